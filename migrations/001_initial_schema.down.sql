@@ -0,0 +1,22 @@
+-- Revert initial schema
+-- Drop tables in reverse order of dependencies
+
+DROP TABLE IF EXISTS user_sessions;
+DROP TABLE IF EXISTS app_secrets;
+DROP TABLE IF EXISTS messages;
+DROP TABLE IF EXISTS auction_bids;
+DROP TABLE IF EXISTS marketplace_listings;
+DROP TABLE IF EXISTS trade_items;
+DROP TABLE IF EXISTS trades;
+DROP TABLE IF EXISTS user_connections;
+DROP TABLE IF EXISTS clan_inventory;
+DROP TABLE IF EXISTS clan_members;
+DROP TABLE IF EXISTS clans;
+DROP TABLE IF EXISTS user_item_modifiers;
+DROP TABLE IF EXISTS user_inventory;
+DROP TABLE IF EXISTS inventory_items;
+DROP TABLE IF EXISTS item_modifiers;
+DROP TABLE IF EXISTS modifier_categories;
+DROP TABLE IF EXISTS item_rarities;
+DROP TABLE IF EXISTS user_games;
+DROP TABLE IF EXISTS users;
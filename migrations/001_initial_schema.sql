@@ -1,29 +1,12 @@
 -- Bridge Database Schema
 -- Initial migration
 
--- Drop tables in reverse order of dependencies to allow clean re-runs
-DROP TABLE IF EXISTS user_sessions;
-DROP TABLE IF EXISTS app_secrets;
-DROP TABLE IF EXISTS messages;
-DROP TABLE IF EXISTS auction_bids;
-DROP TABLE IF EXISTS marketplace_listings;
-DROP TABLE IF EXISTS trade_items;
-DROP TABLE IF EXISTS trades;
-DROP TABLE IF EXISTS user_connections;
-DROP TABLE IF EXISTS clan_inventory;
-DROP TABLE IF EXISTS clan_members;
-DROP TABLE IF EXISTS clans;
-DROP TABLE IF EXISTS user_item_modifiers;
-DROP TABLE IF EXISTS user_inventory;
-DROP TABLE IF EXISTS inventory_items;
-DROP TABLE IF EXISTS item_modifiers;
-DROP TABLE IF EXISTS modifier_categories;
-DROP TABLE IF EXISTS item_rarities;
-DROP TABLE IF EXISTS user_games;
-DROP TABLE IF EXISTS users;
+-- Tables are created with IF NOT EXISTS so databases that predate the
+-- schema_migrations tracking table can be brought under version control
+-- without losing data
 
 -- Users table
-CREATE TABLE users (
+CREATE TABLE IF NOT EXISTS users (
     id CHAR(36) PRIMARY KEY,
     email VARCHAR(255) UNIQUE NOT NULL,
     username VARCHAR(50) UNIQUE NOT NULL,
@@ -40,7 +23,7 @@ CREATE TABLE users (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Item rarity enum
-CREATE TABLE item_rarities (
+CREATE TABLE IF NOT EXISTS item_rarities (
     id INT PRIMARY KEY AUTO_INCREMENT,
     name VARCHAR(50) UNIQUE NOT NULL,
     color VARCHAR(7) NOT NULL, -- hex color
@@ -49,7 +32,7 @@ CREATE TABLE item_rarities (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Insert default rarities
-INSERT INTO item_rarities (name, color, weight) VALUES
+INSERT IGNORE INTO item_rarities (name, color, weight) VALUES
 ('Common', '#B0B0B0', 1000),
 ('Uncommon', '#1EFF00', 400),
 ('Rare', '#0080FF', 150),
@@ -58,7 +41,7 @@ INSERT INTO item_rarities (name, color, weight) VALUES
 ('Mythic', '#FF0080', 1);
 
 -- Modifier categories
-CREATE TABLE modifier_categories (
+CREATE TABLE IF NOT EXISTS modifier_categories (
     id INT PRIMARY KEY AUTO_INCREMENT,
     name VARCHAR(100) UNIQUE NOT NULL,
     description TEXT,
@@ -66,7 +49,7 @@ CREATE TABLE modifier_categories (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Insert default modifier categories
-INSERT INTO modifier_categories (name, description) VALUES
+INSERT IGNORE INTO modifier_categories (name, description) VALUES
 ('Offensive', 'Modifiers that increase damage output'),
 ('Defensive', 'Modifiers that improve survivability'),
 ('Utility', 'Modifiers that provide various utilities'),
@@ -74,7 +57,7 @@ INSERT INTO modifier_categories (name, description) VALUES
 ('Special', 'Unique and rare modifiers');
 
 -- Item modifiers (roguelike perks)
-CREATE TABLE item_modifiers (
+CREATE TABLE IF NOT EXISTS item_modifiers (
     id INT PRIMARY KEY AUTO_INCREMENT,
     category_id INT NOT NULL,
     name VARCHAR(100) NOT NULL,
@@ -89,7 +72,7 @@ CREATE TABLE item_modifiers (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Base item definitions
-CREATE TABLE inventory_items (
+CREATE TABLE IF NOT EXISTS inventory_items (
     id INT PRIMARY KEY AUTO_INCREMENT,
     name VARCHAR(255) NOT NULL,
     description TEXT,
@@ -106,7 +89,7 @@ CREATE TABLE inventory_items (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- User inventory (instances of items with modifiers)
-CREATE TABLE user_inventory (
+CREATE TABLE IF NOT EXISTS user_inventory (
     id CHAR(36) PRIMARY KEY,
     user_id CHAR(36) NOT NULL,
     item_id INT NOT NULL,
@@ -122,7 +105,7 @@ CREATE TABLE user_inventory (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Applied modifiers to user items
-CREATE TABLE user_item_modifiers (
+CREATE TABLE IF NOT EXISTS user_item_modifiers (
     id INT PRIMARY KEY AUTO_INCREMENT,
     user_item_id CHAR(36) NOT NULL,
     modifier_id INT NOT NULL,
@@ -135,7 +118,7 @@ CREATE TABLE user_item_modifiers (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Clans
-CREATE TABLE clans (
+CREATE TABLE IF NOT EXISTS clans (
     id CHAR(36) PRIMARY KEY,
     name VARCHAR(100) UNIQUE NOT NULL,
     tag VARCHAR(10) UNIQUE NOT NULL,
@@ -155,7 +138,7 @@ CREATE TABLE clans (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Clan members
-CREATE TABLE clan_members (
+CREATE TABLE IF NOT EXISTS clan_members (
     id INT PRIMARY KEY AUTO_INCREMENT,
     clan_id CHAR(36) NOT NULL,
     user_id CHAR(36) NOT NULL,
@@ -171,7 +154,7 @@ CREATE TABLE clan_members (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Clan inventory (shared items)
-CREATE TABLE clan_inventory (
+CREATE TABLE IF NOT EXISTS clan_inventory (
     id CHAR(36) PRIMARY KEY,
     clan_id CHAR(36) NOT NULL,
     item_id INT NOT NULL,
@@ -185,7 +168,7 @@ CREATE TABLE clan_inventory (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Friends/Social connections
-CREATE TABLE user_connections (
+CREATE TABLE IF NOT EXISTS user_connections (
     id INT PRIMARY KEY AUTO_INCREMENT,
     user_id CHAR(36) NOT NULL,
     connected_user_id CHAR(36) NOT NULL,
@@ -200,7 +183,7 @@ CREATE TABLE user_connections (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Direct trades between players
-CREATE TABLE trades (
+CREATE TABLE IF NOT EXISTS trades (
     id CHAR(36) PRIMARY KEY,
     initiator_id CHAR(36) NOT NULL,
     recipient_id CHAR(36) NOT NULL,
@@ -216,7 +199,7 @@ CREATE TABLE trades (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Trade items
-CREATE TABLE trade_items (
+CREATE TABLE IF NOT EXISTS trade_items (
     id INT PRIMARY KEY AUTO_INCREMENT,
     trade_id CHAR(36) NOT NULL,
     user_item_id CHAR(36) NOT NULL,
@@ -229,7 +212,7 @@ CREATE TABLE trade_items (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Marketplace/Auction House listings
-CREATE TABLE marketplace_listings (
+CREATE TABLE IF NOT EXISTS marketplace_listings (
     id CHAR(36) PRIMARY KEY,
     seller_id CHAR(36) NOT NULL,
     user_item_id CHAR(36) NOT NULL,
@@ -253,7 +236,7 @@ CREATE TABLE marketplace_listings (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Auction bids
-CREATE TABLE auction_bids (
+CREATE TABLE IF NOT EXISTS auction_bids (
     id INT PRIMARY KEY AUTO_INCREMENT,
     listing_id CHAR(36) NOT NULL,
     bidder_id CHAR(36) NOT NULL,
@@ -267,7 +250,7 @@ CREATE TABLE auction_bids (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Messages
-CREATE TABLE messages (
+CREATE TABLE IF NOT EXISTS messages (
     id CHAR(36) PRIMARY KEY,
     sender_id CHAR(36) NOT NULL,
     recipient_id CHAR(36) NOT NULL,
@@ -285,7 +268,7 @@ CREATE TABLE messages (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Application secrets and configuration
-CREATE TABLE app_secrets (
+CREATE TABLE IF NOT EXISTS app_secrets (
     id INT PRIMARY KEY AUTO_INCREMENT,
     key_name VARCHAR(100) UNIQUE NOT NULL,
     value TEXT NOT NULL,
@@ -296,7 +279,7 @@ CREATE TABLE app_secrets (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- User sessions for auth
-CREATE TABLE user_sessions (
+CREATE TABLE IF NOT EXISTS user_sessions (
     id CHAR(36) PRIMARY KEY,
     user_id CHAR(36) NOT NULL,
     token_hash VARCHAR(255) NOT NULL,
@@ -312,7 +295,7 @@ CREATE TABLE user_sessions (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
 -- Game links (for tracking which games users play)
-CREATE TABLE user_games (
+CREATE TABLE IF NOT EXISTS user_games (
     id INT PRIMARY KEY AUTO_INCREMENT,
     user_id CHAR(36) NOT NULL,
     game_name VARCHAR(100) NOT NULL,
@@ -323,4 +306,4 @@ CREATE TABLE user_games (
     UNIQUE KEY unique_user_game (user_id, game_name),
     INDEX idx_user (user_id),
     INDEX idx_game (game_name)
-) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
\ No newline at end of file
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
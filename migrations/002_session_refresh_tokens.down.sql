@@ -0,0 +1,9 @@
+-- Revert refresh token rotation columns
+
+ALTER TABLE user_sessions ADD INDEX idx_token (token_hash);
+
+ALTER TABLE user_sessions
+    DROP INDEX idx_previous_token,
+    DROP INDEX idx_token_unique,
+    DROP COLUMN revoked_at,
+    DROP COLUMN previous_token_hash;
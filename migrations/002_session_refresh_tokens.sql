@@ -0,0 +1,11 @@
+-- Refresh token rotation for user sessions
+-- token_hash holds the SHA-256 of the current refresh token, previous_token_hash
+-- the one it replaced so that replaying a rotated token can be detected
+
+ALTER TABLE user_sessions
+    ADD COLUMN previous_token_hash VARCHAR(255) NULL AFTER token_hash,
+    ADD COLUMN revoked_at TIMESTAMP NULL AFTER ip_address,
+    ADD UNIQUE INDEX idx_token_unique (token_hash),
+    ADD INDEX idx_previous_token (previous_token_hash);
+
+ALTER TABLE user_sessions DROP INDEX idx_token;
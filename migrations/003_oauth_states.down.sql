@@ -0,0 +1,3 @@
+-- Revert pending OAuth2 authorization requests
+
+DROP TABLE IF EXISTS oauth_states;
@@ -0,0 +1,14 @@
+-- Pending OAuth2 authorization requests
+-- Holds the CSRF state and PKCE verifier between /start and /callback;
+-- user_id is set when an authenticated user is linking a provider account
+
+CREATE TABLE oauth_states (
+    state VARCHAR(128) PRIMARY KEY,
+    provider VARCHAR(50) NOT NULL,
+    pkce_verifier VARCHAR(128) NOT NULL,
+    user_id CHAR(36),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    INDEX idx_expires (expires_at)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
@@ -0,0 +1,15 @@
+-- Revert generic user identities back to users.discord_id
+
+DELETE FROM oauth_states WHERE pkce_verifier IS NULL;
+
+ALTER TABLE oauth_states MODIFY pkce_verifier VARCHAR(128) NOT NULL;
+
+ALTER TABLE users
+    ADD COLUMN discord_id VARCHAR(100) UNIQUE AFTER avatar_url,
+    ADD INDEX idx_discord_id (discord_id);
+
+UPDATE users u
+JOIN user_identities i ON i.user_id = u.id AND i.provider = 'discord'
+SET u.discord_id = i.provider_user_id;
+
+DROP TABLE IF EXISTS user_identities;
@@ -0,0 +1,26 @@
+-- External identities linked to users
+-- Replaces the single users.discord_id column so any number of identity
+-- providers can be linked to an account
+
+CREATE TABLE user_identities (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    user_id CHAR(36) NOT NULL,
+    provider VARCHAR(50) NOT NULL, -- discord, steam, launcher, etc.
+    provider_user_id VARCHAR(255) NOT NULL,
+    username VARCHAR(255),
+    email VARCHAR(255),
+    linked_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_login_at TIMESTAMP NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE KEY unique_provider_identity (provider, provider_user_id),
+    UNIQUE KEY unique_user_provider (user_id, provider),
+    INDEX idx_user (user_id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+INSERT INTO user_identities (user_id, provider, provider_user_id, linked_at)
+SELECT id, 'discord', discord_id, updated_at FROM users WHERE discord_id IS NOT NULL;
+
+ALTER TABLE users DROP INDEX idx_discord_id, DROP INDEX discord_id, DROP COLUMN discord_id;
+
+-- Not every provider uses PKCE (e.g. OpenID 2.0)
+ALTER TABLE oauth_states MODIFY pkce_verifier VARCHAR(128) NULL;
@@ -0,0 +1,3 @@
+-- Revert single-use user tokens
+
+DROP TABLE IF EXISTS user_tokens;
@@ -0,0 +1,5 @@
+-- Revert email verification
+
+DELETE FROM user_tokens WHERE purpose = 'email_verification';
+
+ALTER TABLE users DROP COLUMN email_verified_at;
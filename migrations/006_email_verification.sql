@@ -0,0 +1,6 @@
+-- Email verification for registrations
+-- Accounts that existed before verification was introduced are treated as verified
+
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMP NULL AFTER email;
+
+UPDATE users SET email_verified_at = created_at;
@@ -0,0 +1,4 @@
+-- Revert TOTP two-factor authentication
+
+DROP TABLE IF EXISTS user_recovery_codes;
+DROP TABLE IF EXISTS user_totp;
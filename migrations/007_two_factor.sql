@@ -0,0 +1,22 @@
+-- TOTP two-factor authentication
+-- A secret is stored as soon as enrollment starts but only enforced once confirmed
+
+CREATE TABLE user_totp (
+    user_id CHAR(36) PRIMARY KEY,
+    secret VARCHAR(64) NOT NULL, -- base32
+    enabled_at TIMESTAMP NULL, -- NULL while enrollment is pending
+    last_used_step BIGINT NULL, -- time step of the last accepted code, blocks replays
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+-- Single-use recovery codes, only the SHA-256 hash of each code is stored
+CREATE TABLE user_recovery_codes (
+    id CHAR(36) PRIMARY KEY,
+    user_id CHAR(36) NOT NULL,
+    code_hash CHAR(64) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    used_at TIMESTAMP NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE KEY unique_user_code (user_id, code_hash)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
@@ -0,0 +1,3 @@
+-- Revert the login attempt audit log
+
+DROP TABLE IF EXISTS login_attempts;
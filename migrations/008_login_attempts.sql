@@ -0,0 +1,17 @@
+-- Audit log of login attempts, also used for brute-force backoff
+-- The submitted email is kept even when it doesn't match an account
+
+CREATE TABLE login_attempts (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    email VARCHAR(255) NOT NULL,
+    user_id CHAR(36) NULL,
+    ip_address VARCHAR(45) NULL,
+    user_agent VARCHAR(500) NULL,
+    success BOOLEAN NOT NULL,
+    outcome VARCHAR(32) NOT NULL, -- success, mfa_required, invalid_password, invalid_mfa_code, unknown_email, locked
+    attempted_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL,
+    INDEX idx_email_attempted (email, attempted_at),
+    INDEX idx_ip_attempted (ip_address, attempted_at),
+    INDEX idx_user_attempted (user_id, attempted_at)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
@@ -0,0 +1,10 @@
+-- Revert to a single jwt_secret
+-- Tokens signed by keys other than the original secret stop validating
+
+UPDATE app_secrets
+SET key_name = 'jwt_secret', description = 'Auto-generated secret for jwt_secret'
+WHERE key_name = 'jwt_signing_key:legacy';
+
+DELETE FROM app_secrets WHERE key_name LIKE 'jwt\_signing\_key:%';
+
+ALTER TABLE app_secrets DROP COLUMN retired_at;
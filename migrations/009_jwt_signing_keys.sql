@@ -0,0 +1,10 @@
+-- Rotating JWT signing keys
+-- Keys are stored in app_secrets as jwt_signing_key:<kid>; the newest unretired key
+-- signs tokens and retired keys keep validating for a grace period
+
+ALTER TABLE app_secrets ADD COLUMN retired_at TIMESTAMP NULL AFTER description;
+
+-- Keep the existing secret so tokens issued before this migration stay valid
+UPDATE app_secrets
+SET key_name = 'jwt_signing_key:legacy', description = 'JWT signing key (adopted from jwt_secret)'
+WHERE key_name = 'jwt_secret';
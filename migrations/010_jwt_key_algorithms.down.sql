@@ -0,0 +1,6 @@
+-- Revert to HS256-only signing keys
+-- A new HS256 key is generated on startup if no active one is left
+
+DELETE FROM app_secrets WHERE key_name LIKE 'jwt\_signing\_key:%' AND algorithm <> 'HS256';
+
+ALTER TABLE app_secrets DROP COLUMN algorithm;
@@ -0,0 +1,6 @@
+-- Asymmetric JWT signing keys
+-- HS256 keys hold a shared secret; EdDSA and RS256 keys hold a PKCS#8 PEM private key
+
+ALTER TABLE app_secrets ADD COLUMN algorithm VARCHAR(16) NULL AFTER value;
+
+UPDATE app_secrets SET algorithm = 'HS256' WHERE key_name LIKE 'jwt\_signing\_key:%';
@@ -0,0 +1,5 @@
+-- Revert game server API keys
+
+DELETE FROM permissions WHERE name IN ('inventory:read', 'game_clients:manage');
+
+DROP TABLE IF EXISTS game_clients;
@@ -0,0 +1,25 @@
+-- API keys for game servers acting on behalf of players
+-- Keys are shown once at creation; only their SHA-256 hash is stored
+
+CREATE TABLE game_clients (
+    id CHAR(36) PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL, -- first characters of the key, to tell keys apart
+    key_hash CHAR(64) UNIQUE NOT NULL,
+    scopes VARCHAR(500) NOT NULL, -- space separated, e.g. "items:grant inventory:read"
+    allowed_games VARCHAR(1000) NOT NULL, -- space separated game names, or "*" for any game
+    created_by CHAR(36) NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP NULL,
+    revoked_at TIMESTAMP NULL,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL,
+    INDEX idx_revoked (revoked_at)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+INSERT INTO permissions (name, description) VALUES
+('inventory:read', 'Read any player''s inventory'),
+('game_clients:manage', 'Create, list and revoke game server API keys');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM roles r JOIN permissions p ON p.name IN ('inventory:read', 'game_clients:manage')
+WHERE r.name = 'admin';
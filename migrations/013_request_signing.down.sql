@@ -0,0 +1,3 @@
+-- Revert HMAC request signing for game clients
+
+ALTER TABLE game_clients DROP COLUMN signing_secret;
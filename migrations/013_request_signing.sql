@@ -0,0 +1,5 @@
+-- HMAC request signing for game clients
+-- The server must be able to recompute signatures, so the secret is stored as-is
+-- (like the JWT secrets in app_secrets) and only ever returned when generated
+
+ALTER TABLE game_clients ADD COLUMN signing_secret VARCHAR(64) NULL AFTER key_hash;
@@ -0,0 +1,5 @@
+-- Revert self-service account deletion
+
+DROP INDEX idx_deleted ON users;
+
+ALTER TABLE users DROP COLUMN deleted_at;
@@ -0,0 +1,6 @@
+-- Self-service account deletion
+-- Deleted accounts are anonymized immediately and purged after a grace period
+
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMP NULL AFTER is_active;
+
+CREATE INDEX idx_deleted ON users (deleted_at);
@@ -0,0 +1,3 @@
+-- Revert profile privacy settings
+
+DROP TABLE IF EXISTS user_privacy_settings;
@@ -0,0 +1,12 @@
+-- Per-user privacy settings for public profiles
+-- Each section is visible to: everyone, friends, clan, friends_and_clan or private
+-- Users without a row get the column defaults
+
+CREATE TABLE user_privacy_settings (
+    user_id CHAR(36) PRIMARY KEY,
+    clan_visibility VARCHAR(20) NOT NULL DEFAULT 'everyone',
+    games_visibility VARCHAR(20) NOT NULL DEFAULT 'friends_and_clan',
+    showcase_visibility VARCHAR(20) NOT NULL DEFAULT 'everyone',
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
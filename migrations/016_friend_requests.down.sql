@@ -0,0 +1,5 @@
+-- Revert friend requests and presence
+
+ALTER TABLE users DROP COLUMN last_seen_at;
+
+DROP TABLE IF EXISTS friend_requests;
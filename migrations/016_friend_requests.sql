@@ -0,0 +1,16 @@
+-- Friend requests and presence
+-- Accepted requests become a pair of 'friend' rows in user_connections, one per direction
+
+CREATE TABLE friend_requests (
+    id CHAR(36) PRIMARY KEY,
+    sender_id CHAR(36) NOT NULL,
+    recipient_id CHAR(36) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (sender_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (recipient_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE KEY unique_friend_request (sender_id, recipient_id),
+    INDEX idx_recipient (recipient_id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+-- Refreshed at most once a minute by authenticated requests
+ALTER TABLE users ADD COLUMN last_seen_at TIMESTAMP NULL AFTER last_login;
@@ -0,0 +1,3 @@
+-- Revert inbound trade policies
+
+DROP TABLE IF EXISTS user_trade_settings;
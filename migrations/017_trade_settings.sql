@@ -0,0 +1,11 @@
+-- Inbound trade policies and trusted traders
+-- Trusted traders are 'trader' rows in user_connections, from the user who trusts to the trusted user
+-- inbound_policy: anyone, friends, trusted_traders, clanmates or nobody
+
+CREATE TABLE user_trade_settings (
+    user_id CHAR(36) PRIMARY KEY,
+    inbound_policy VARCHAR(20) NOT NULL DEFAULT 'anyone',
+    auto_accept_trusted_gifts BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
@@ -0,0 +1,5 @@
+-- Revert game account linking
+
+ALTER TABLE user_games DROP INDEX unique_game_account;
+
+DROP TABLE IF EXISTS game_link_codes;
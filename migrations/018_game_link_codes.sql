@@ -0,0 +1,20 @@
+-- Game account linking
+-- Game servers issue a short-lived code to a player in game; the player enters it
+-- on Bridge to prove they own the game account. Only the code's SHA-256 hash is stored.
+
+CREATE TABLE game_link_codes (
+    id CHAR(36) PRIMARY KEY,
+    code_hash CHAR(64) UNIQUE NOT NULL,
+    game_name VARCHAR(100) NOT NULL,
+    game_user_id VARCHAR(255) NOT NULL,
+    client_id CHAR(36) NULL, -- game client that issued the code
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (client_id) REFERENCES game_clients(id) ON DELETE SET NULL,
+    INDEX idx_game_account (game_name, game_user_id),
+    INDEX idx_expires (expires_at)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+-- A game account maps to at most one Bridge account
+ALTER TABLE user_games ADD UNIQUE KEY unique_game_account (game_name, game_user_id);
@@ -0,0 +1,15 @@
+-- Revert the multi-game catalog
+
+DELETE FROM permissions WHERE name = 'games:manage';
+
+DROP TABLE IF EXISTS item_game_rules;
+
+ALTER TABLE inventory_items
+    DROP FOREIGN KEY fk_inventory_items_game,
+    DROP INDEX unique_item_key,
+    DROP COLUMN item_key,
+    DROP COLUMN game_id;
+
+ALTER TABLE user_games DROP FOREIGN KEY fk_user_games_game;
+
+DROP TABLE IF EXISTS games;
@@ -0,0 +1,54 @@
+-- Multi-game catalog and per-game items
+-- Games are identified by `name`, the value used in user_games.game_name,
+-- game client allowed games and API paths. Items with no game_id are
+-- Bridge-wide; item_key is unique within each game's namespace.
+
+CREATE TABLE games (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    name VARCHAR(100) UNIQUE NOT NULL,
+    display_name VARCHAR(255) NOT NULL,
+    is_active BOOLEAN DEFAULT TRUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+-- Register the games players have already linked or been issued codes for
+INSERT IGNORE INTO games (name, display_name)
+SELECT DISTINCT game_name, game_name FROM user_games;
+
+INSERT IGNORE INTO games (name, display_name)
+SELECT DISTINCT game_name, game_name FROM game_link_codes;
+
+ALTER TABLE user_games
+    ADD CONSTRAINT fk_user_games_game FOREIGN KEY (game_name) REFERENCES games(name) ON UPDATE CASCADE;
+
+ALTER TABLE inventory_items
+    ADD COLUMN game_id INT NULL AFTER id,
+    ADD COLUMN item_key VARCHAR(100) NULL AFTER game_id,
+    ADD CONSTRAINT fk_inventory_items_game FOREIGN KEY (game_id) REFERENCES games(id),
+    ADD UNIQUE KEY unique_item_key (game_id, item_key);
+
+UPDATE inventory_items SET item_key = CONCAT('item-', id);
+
+ALTER TABLE inventory_items MODIFY item_key VARCHAR(100) NOT NULL;
+
+-- Where an item may be used or shown outside its own game. Items are always
+-- eligible in their own game; Bridge-wide items are eligible everywhere unless
+-- a rule says otherwise.
+CREATE TABLE item_game_rules (
+    item_id INT NOT NULL,
+    game_id INT NOT NULL,
+    can_use BOOLEAN NOT NULL DEFAULT FALSE,
+    can_show BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (item_id, game_id),
+    FOREIGN KEY (item_id) REFERENCES inventory_items(id) ON DELETE CASCADE,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE,
+    INDEX idx_game (game_id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+INSERT INTO permissions (name, description) VALUES
+('games:manage', 'Add games to the catalog');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM roles r JOIN permissions p ON p.name = 'games:manage'
+WHERE r.name = 'admin';
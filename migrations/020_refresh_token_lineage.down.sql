@@ -0,0 +1,3 @@
+-- Revert refresh token lineage
+
+DROP TABLE IF EXISTS session_refresh_tokens;
@@ -0,0 +1,14 @@
+-- Refresh token lineage
+-- Every refresh token a session has rotated past, so replaying any earlier
+-- token, not only the one just replaced, is detected and revokes the session
+
+CREATE TABLE session_refresh_tokens (
+    token_hash CHAR(64) PRIMARY KEY,
+    session_id CHAR(36) NOT NULL,
+    rotated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (session_id) REFERENCES user_sessions(id) ON DELETE CASCADE,
+    INDEX idx_session (session_id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+INSERT IGNORE INTO session_refresh_tokens (token_hash, session_id)
+SELECT previous_token_hash, id FROM user_sessions WHERE previous_token_hash IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Revert single-use MFA challenges
+
+DROP TABLE IF EXISTS used_mfa_challenges;
@@ -0,0 +1,10 @@
+-- Single-use MFA challenges
+-- The jti of every challenge token that completed a login, kept until the token
+-- expires so the same challenge can't be traded for a second session
+
+CREATE TABLE used_mfa_challenges (
+    jti CHAR(36) PRIMARY KEY,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_expires (expires_at)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
@@ -0,0 +1,3 @@
+-- Revert clan invites
+
+DROP TABLE IF EXISTS clan_invites;
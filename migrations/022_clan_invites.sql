@@ -0,0 +1,15 @@
+-- Clan invites
+-- Leaders and officers invite players; the player joins by accepting
+
+CREATE TABLE clan_invites (
+    id CHAR(36) PRIMARY KEY,
+    clan_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    invited_by CHAR(36) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (clan_id) REFERENCES clans(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_by) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE KEY unique_invite (clan_id, user_id),
+    INDEX idx_user (user_id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
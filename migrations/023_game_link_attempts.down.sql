@@ -0,0 +1,3 @@
+-- Revert game link code attempts
+
+DROP TABLE IF EXISTS game_link_attempts;
@@ -0,0 +1,13 @@
+-- Game link code attempts
+-- Every code a user submits, so wrong guesses can be throttled per user and per IP
+
+CREATE TABLE game_link_attempts (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id CHAR(36) NOT NULL,
+    ip_address VARCHAR(45) NULL,
+    success BOOLEAN NOT NULL,
+    attempted_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    INDEX idx_user_attempted (user_id, attempted_at),
+    INDEX idx_ip_attempted (ip_address, attempted_at)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
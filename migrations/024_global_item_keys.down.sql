@@ -0,0 +1,5 @@
+-- Revert unique keys for Bridge-wide items
+
+ALTER TABLE inventory_items
+    DROP INDEX unique_global_item_key,
+    DROP COLUMN global_item_key;
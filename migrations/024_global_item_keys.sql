@@ -0,0 +1,8 @@
+-- Unique keys for Bridge-wide items
+-- unique_item_key (game_id, item_key) doesn't cover items whose game_id is
+-- NULL, so a generated column holds the key of Bridge-wide items only and
+-- carries its own unique index
+
+ALTER TABLE inventory_items
+    ADD COLUMN global_item_key VARCHAR(100) AS (IF(game_id IS NULL, item_key, NULL)) STORED,
+    ADD UNIQUE KEY unique_global_item_key (global_item_key);
@@ -0,0 +1,10 @@
+-- Restore user_sessions.previous_token_hash from the latest rotated token
+
+ALTER TABLE user_sessions
+    ADD COLUMN previous_token_hash VARCHAR(255) NULL AFTER token_hash,
+    ADD INDEX idx_previous_token (previous_token_hash);
+
+UPDATE user_sessions s
+JOIN session_refresh_tokens t ON t.session_id = s.id
+SET s.previous_token_hash = t.token_hash
+WHERE t.rotated_at = (SELECT MAX(r.rotated_at) FROM session_refresh_tokens r WHERE r.session_id = s.id);
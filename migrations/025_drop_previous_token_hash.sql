@@ -0,0 +1,7 @@
+-- Drop user_sessions.previous_token_hash
+-- Rotated refresh tokens are tracked in session_refresh_tokens, so the column
+-- was only written and never read
+
+ALTER TABLE user_sessions
+    DROP INDEX idx_previous_token,
+    DROP COLUMN previous_token_hash;
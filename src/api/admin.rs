@@ -0,0 +1,195 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    api::middleware::{auth::AuthUser, permissions::RequirePermission},
+    models::{
+        AssignRoleRequest, CreateGameClientRequest, CreateGameClientResponse, GameClientInfo,
+        LoginAttempt, LoginAttemptFilter, RoleWithPermissions, SigningSecretResponse,
+        UserRolesResponse,
+    },
+    services::{game_clients, roles},
+    utils::{errors::Result, AppError},
+    AppState,
+};
+
+const DEFAULT_LOGIN_ATTEMPT_LIMIT: i64 = 100;
+const MAX_LOGIN_ATTEMPT_LIMIT: i64 = 500;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/roles",
+            get(list_roles).route_layer(RequirePermission("roles:manage")),
+        )
+        .route(
+            "/users/:id/roles",
+            get(get_user_roles)
+                .post(assign_role)
+                .route_layer(RequirePermission("roles:manage")),
+        )
+        .route(
+            "/users/:id/roles/:role",
+            delete(remove_role).route_layer(RequirePermission("roles:manage")),
+        )
+        .route(
+            "/game-clients",
+            get(list_game_clients)
+                .post(create_game_client)
+                .route_layer(RequirePermission("game_clients:manage")),
+        )
+        .route(
+            "/game-clients/:id",
+            delete(revoke_game_client).route_layer(RequirePermission("game_clients:manage")),
+        )
+        .route(
+            "/game-clients/:id/signing-secret",
+            post(rotate_game_client_signing_secret)
+                .route_layer(RequirePermission("game_clients:manage")),
+        )
+        .route(
+            "/login-attempts",
+            get(list_login_attempts).route_layer(RequirePermission("audit:read")),
+        )
+}
+
+async fn list_roles(State(state): State<Arc<AppState>>) -> Result<Json<Vec<RoleWithPermissions>>> {
+    Ok(Json(roles::list_roles(&state.db).await?))
+}
+
+async fn get_user_roles(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> Result<Json<UserRolesResponse>> {
+    let roles = roles::user_roles(&state.db, &user_id).await?;
+
+    Ok(Json(UserRolesResponse { user_id, roles }))
+}
+
+/// Takes effect when the user's next access token is issued
+async fn assign_role(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(user_id): Path<String>,
+    Json(payload): Json<AssignRoleRequest>,
+) -> Result<Json<UserRolesResponse>> {
+    roles::assign_role(&state.db, &user_id, &payload.role, Some(&auth.user_id)).await?;
+
+    let roles = roles::user_roles(&state.db, &user_id).await?;
+
+    Ok(Json(UserRolesResponse { user_id, roles }))
+}
+
+/// Takes effect immediately, since permissions are checked against current roles
+async fn remove_role(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path((user_id, role)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    // Keep admins from locking themselves out
+    if user_id == auth.user_id && role == "admin" {
+        return Err(AppError::bad_request(
+            "You can't remove your own admin role",
+        ));
+    }
+
+    if !roles::remove_role(&state.db, &user_id, &role).await? {
+        return Err(AppError::NotFound(format!(
+            "User does not have the '{}' role",
+            role
+        )));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_game_clients(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<GameClientInfo>>> {
+    let clients = game_clients::list_clients(&state.db).await?;
+
+    Ok(Json(
+        clients.iter().map(|client| client.to_info()).collect(),
+    ))
+}
+
+/// The API key and signing secret are only ever returned here
+async fn create_game_client(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<CreateGameClientRequest>,
+) -> Result<(StatusCode, Json<CreateGameClientResponse>)> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let created = game_clients::create_client(&state.db, &payload, &auth.user_id).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateGameClientResponse {
+            client: created.client.to_info(),
+            api_key: created.api_key,
+            signing_secret: created.signing_secret,
+        }),
+    ))
+}
+
+async fn revoke_game_client(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<GameClientInfo>> {
+    let client = game_clients::revoke_client(&state.db, &id).await?;
+
+    Ok(Json(client.to_info()))
+}
+
+/// Issue a new request signing secret, e.g. for clients created before signing existed
+async fn rotate_game_client_signing_secret(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<SigningSecretResponse>> {
+    let signing_secret = game_clients::rotate_signing_secret(&state.db, &id).await?;
+
+    Ok(Json(SigningSecretResponse {
+        key_id: id,
+        signing_secret,
+    }))
+}
+
+/// Most recent entries of the login audit log, optionally filtered
+async fn list_login_attempts(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<LoginAttemptFilter>,
+) -> Result<Json<Vec<LoginAttempt>>> {
+    let limit = filter
+        .limit
+        .unwrap_or(DEFAULT_LOGIN_ATTEMPT_LIMIT)
+        .clamp(1, MAX_LOGIN_ATTEMPT_LIMIT);
+
+    let attempts = sqlx::query_as(
+        "SELECT * FROM login_attempts
+         WHERE (? IS NULL OR email = ?)
+           AND (? IS NULL OR user_id = ?)
+           AND (? IS NULL OR ip_address = ?)
+         ORDER BY attempted_at DESC, id DESC
+         LIMIT ?",
+    )
+    .bind(&filter.email)
+    .bind(&filter.email)
+    .bind(&filter.user_id)
+    .bind(&filter.user_id)
+    .bind(&filter.ip_address)
+    .bind(&filter.ip_address)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(attempts))
+}
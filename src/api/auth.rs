@@ -1,20 +1,58 @@
-use axum::{extract::State, routing::post, Json, Router};
-use std::sync::Arc;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
-    models::{CreateUserRequest, LoginRequest, LoginResponse, User},
-    utils::{self, errors::Result},
+    api::{
+        extractors::ClientInfo,
+        middleware::auth::{self, AuthUser},
+    },
+    auth::IdentityProvider,
+    models::{
+        AuthTokens, CreateUserRequest, ForgotPasswordRequest, LoginRequest, LoginResponse,
+        MfaChallenge, MfaLoginRequest, OAuthCallbackResponse, OAuthStartResponse, OAuthState,
+        RefreshTokenRequest, ResetPasswordRequest, User, VerifyEmailRequest,
+    },
+    services::{
+        identities,
+        login_attempts::{self, LoginOutcome},
+        mailer::Email,
+        mfa, roles, sessions,
+        tokens::{self, TokenPurpose},
+    },
+    utils::{self, errors::Result, AppError},
     AppState,
 };
 
+/// How long a user has to complete the provider's consent screen
+const OAUTH_STATE_TTL_MINUTES: i64 = 10;
+
+/// Minimum time between verification emails for the same account
+const VERIFICATION_RESEND_COOLDOWN_SECONDS: i64 = 60;
+
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
+        .route("/login/mfa", post(login_mfa))
+        .route("/refresh", post(refresh))
+        .route("/logout", post(logout))
+        .route("/password/forgot", post(forgot_password))
+        .route("/password/reset", post(reset_password))
+        .route("/verify-email", post(verify_email))
+        .route("/verify-email/resend", post(resend_verification_email))
+        .route("/:provider/start", get(provider_start))
+        .route("/:provider/callback", get(provider_callback))
 }
 
 async fn register(
     State(state): State<Arc<AppState>>,
+    client: ClientInfo,
     Json(payload): Json<CreateUserRequest>,
 ) -> Result<Json<LoginResponse>> {
     // Validate request
@@ -24,7 +62,7 @@ async fn register(
         .map_err(|e| utils::AppError::Validation(e.to_string()))?;
 
     // Hash password
-    let password_hash = utils::hash_password(&payload.password)?;
+    let password_hash = state.passwords.hash(&payload.password)?;
 
     // Create user
     let user = User::new(payload.email, payload.username, password_hash);
@@ -48,38 +86,553 @@ async fn register(
         }
     })?;
 
-    // Generate JWT
-    let token = utils::generate_jwt(&user.id, &state.jwt_secret)?;
+    // Ask the user to confirm they own the address
+    send_verification_email(&state, &user).await?;
 
-    Ok(Json(LoginResponse { user, token }))
+    // Start a session
+    let tokens = start_session(&state, &user.id, client).await?;
+
+    Ok(Json(LoginResponse::Authenticated { user, tokens }))
 }
 
 async fn login(
     State(state): State<Arc<AppState>>,
+    client: ClientInfo,
     Json(payload): Json<LoginRequest>,
 ) -> Result<Json<LoginResponse>> {
+    check_login_throttle(&state, &payload.email, &client).await?;
+
     // Find user by email
-    let user: User = sqlx::query_as("SELECT * FROM users WHERE email = ? AND is_active = true")
-        .bind(&payload.email)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|_| utils::AppError::Authentication("Invalid email or password".to_string()))?;
+    let user: Option<User> =
+        sqlx::query_as("SELECT * FROM users WHERE email = ? AND is_active = true")
+            .bind(&payload.email)
+            .fetch_optional(&state.db)
+            .await?;
+
+    let Some(user) = user else {
+        // Spend the same time as a real check so unknown emails can't be detected
+        state.passwords.verify_dummy(&payload.password);
+        record_login_attempt(
+            &state,
+            &payload.email,
+            None,
+            &client,
+            LoginOutcome::UnknownEmail,
+        )
+        .await?;
+        return Err(utils::AppError::Authentication(
+            "Invalid email or password".to_string(),
+        ));
+    };
 
     // Verify password
-    if !utils::verify_password(&payload.password, &user.password_hash)? {
+    if !state
+        .passwords
+        .verify(&payload.password, &user.password_hash)?
+    {
+        record_login_attempt(
+            &state,
+            &user.email,
+            Some(&user.id),
+            &client,
+            LoginOutcome::InvalidPassword,
+        )
+        .await?;
         return Err(utils::AppError::Authentication(
             "Invalid email or password".to_string(),
         ));
     }
 
-    // Update last login
+    // Upgrade hashes made under an older policy while the plaintext is at hand
+    if state.passwords.needs_rehash(&user.password_hash) {
+        let password_hash = state.passwords.hash(&payload.password)?;
+        sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+            .bind(&password_hash)
+            .bind(&user.id)
+            .execute(&state.db)
+            .await?;
+        tracing::info!(user_id = %user.id, "Password rehashed with current policy");
+    }
+
+    Ok(Json(complete_login(&state, user, client).await?))
+}
+
+/// Second step of a two-factor login: trade the challenge token and a code for a session
+async fn login_mfa(
+    State(state): State<Arc<AppState>>,
+    client: ClientInfo,
+    Json(payload): Json<MfaLoginRequest>,
+) -> Result<Json<LoginResponse>> {
+    let claims = utils::validate_mfa_challenge(&payload.mfa_token, &state.signing_keys).await?;
+
+    let user: User = sqlx::query_as("SELECT * FROM users WHERE id = ? AND is_active = true")
+        .bind(&claims.sub)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::Authentication("Account is disabled".to_string()))?;
+
+    // Codes are short, so guesses are throttled the same way as passwords
+    check_login_throttle(&state, &user.email, &client).await?;
+
+    if !mfa::verify(&state.db, &user.id, &payload.code).await? {
+        record_login_attempt(
+            &state,
+            &user.email,
+            Some(&user.id),
+            &client,
+            LoginOutcome::InvalidMfaCode,
+        )
+        .await?;
+        return Err(AppError::Authentication(
+            "Invalid two-factor code".to_string(),
+        ));
+    }
+
+    let expires_at = DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now);
+    if !mfa::consume_challenge(&state.db, &claims.jti, expires_at).await? {
+        return Err(AppError::Authentication(
+            "Invalid MFA challenge".to_string(),
+        ));
+    }
+
+    let tokens = establish_login(&state, &user, client).await?;
+
+    Ok(Json(LoginResponse::Authenticated { user, tokens }))
+}
+
+async fn refresh(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<Json<AuthTokens>> {
+    let (session, refresh_token) = sessions::rotate_refresh_token(
+        &state.db,
+        &payload.refresh_token,
+        state.config.refresh_token_ttl,
+    )
+    .await?;
+
+    // Make sure the account wasn't disabled since the session started
+    let active: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM users WHERE id = ? AND is_active = true")
+            .bind(&session.user_id)
+            .fetch_optional(&state.db)
+            .await?;
+
+    if active.is_none() {
+        sessions::revoke_session(&state.db, &session.id).await?;
+        return Err(utils::AppError::Authentication(
+            "Account is disabled".to_string(),
+        ));
+    }
+
+    // Pick up role changes made since the last token was issued
+    let roles = roles::user_roles(&state.db, &session.user_id).await?;
+
+    let token = utils::generate_jwt(
+        &session.user_id,
+        &session.id,
+        roles,
+        &state.signing_keys,
+        state.config.access_token_ttl,
+    )
+    .await?;
+
+    Ok(Json(AuthTokens {
+        token,
+        refresh_token,
+        expires_in: state.config.access_token_ttl.num_seconds(),
+    }))
+}
+
+/// Revoke the session the presented access token belongs to
+async fn logout(State(state): State<Arc<AppState>>, auth_user: AuthUser) -> Result<StatusCode> {
+    sessions::revoke_session(&state.db, &auth_user.session_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn verify_email(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<StatusCode> {
+    let user_id =
+        tokens::consume(&state.db, TokenPurpose::EmailVerification, &payload.token).await?;
+
+    sqlx::query(
+        "UPDATE users SET email_verified_at = NOW() WHERE id = ? AND email_verified_at IS NULL",
+    )
+    .bind(&user_id)
+    .execute(&state.db)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn resend_verification_email(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode> {
+    let user: User = sqlx::query_as("SELECT * FROM users WHERE id = ?")
+        .bind(&auth_user.user_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    if user.is_email_verified() {
+        return Err(AppError::Conflict("Email is already verified".to_string()));
+    }
+
+    let last_sent =
+        tokens::last_issued_at(&state.db, &user.id, TokenPurpose::EmailVerification).await?;
+    if let Some(last_sent) = last_sent {
+        if Utc::now() - last_sent < Duration::seconds(VERIFICATION_RESEND_COOLDOWN_SECONDS) {
+            return Err(AppError::bad_request(
+                "A verification email was sent recently, please wait before requesting another",
+            ));
+        }
+    }
+
+    send_verification_email(&state, &user).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn send_verification_email(state: &AppState, user: &User) -> Result<()> {
+    let token = tokens::issue(
+        &state.db,
+        &user.id,
+        TokenPurpose::EmailVerification,
+        state.config.email_verification_ttl,
+    )
+    .await?;
+
+    let email = Email {
+        to: user.email.clone(),
+        subject: "Verify your Bridge email address".to_string(),
+        body: format!(
+            "Hi {},\n\nConfirm your email address by opening the link below. It expires in {} hours.\n\n\
+             {}/verify-email?token={}",
+            user.username,
+            state.config.email_verification_ttl.num_hours(),
+            state.config.app_url,
+            token
+        ),
+    };
+
+    let mailer = state.mailer.clone();
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(email).await {
+            tracing::error!("Failed to send verification email: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Email a password reset link. Always succeeds so it can't be used to probe
+/// which emails have accounts.
+async fn forgot_password(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<StatusCode> {
+    let user: Option<User> =
+        sqlx::query_as("SELECT * FROM users WHERE email = ? AND is_active = true")
+            .bind(&payload.email)
+            .fetch_optional(&state.db)
+            .await?;
+
+    if let Some(user) = user {
+        let token = tokens::issue(
+            &state.db,
+            &user.id,
+            TokenPurpose::PasswordReset,
+            state.config.password_reset_ttl,
+        )
+        .await?;
+
+        let email = Email {
+            to: user.email,
+            subject: "Reset your Bridge password".to_string(),
+            body: format!(
+                "Hi {},\n\nUse the link below to choose a new password. It expires in {} minutes \
+                 and can only be used once.\n\n{}/reset-password?token={}\n\n\
+                 If you didn't ask to reset your password you can ignore this email.",
+                user.username,
+                state.config.password_reset_ttl.num_minutes(),
+                state.config.app_url,
+                token
+            ),
+        };
+
+        // Send in the background so response timing doesn't reveal whether the account exists
+        let mailer = state.mailer.clone();
+        tokio::spawn(async move {
+            if let Err(e) = mailer.send(email).await {
+                tracing::error!("Failed to send password reset email: {}", e);
+            }
+        });
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn reset_password(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<StatusCode> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let user_id = tokens::consume(&state.db, TokenPurpose::PasswordReset, &payload.token).await?;
+
+    let password_hash = state.passwords.hash(&payload.new_password)?;
+    sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+        .bind(&password_hash)
+        .bind(&user_id)
+        .execute(&state.db)
+        .await?;
+
+    // Anyone holding the old password may have signed in with it
+    let revoked = sessions::revoke_all_sessions(&state.db, &user_id).await?;
+    tracing::info!(user_id = %user_id, revoked, "Password reset, sessions revoked");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Begin an identity provider's authorization flow.
+///
+/// When called with a valid bearer token the external account is linked to the
+/// caller instead of being used to log in.
+async fn provider_start(
+    State(state): State<Arc<AppState>>,
+    Path(provider_name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<OAuthStartResponse>> {
+    let provider = identity_provider(&state, &provider_name)?;
+
+    let link_user_id = if headers.contains_key(header::AUTHORIZATION) {
+        Some(auth::authenticate(&state, &headers).await?.user_id)
+    } else {
+        None
+    };
+
+    let csrf_state = utils::generate_session_token();
+    let request = provider.authorize(&csrf_state)?;
+
+    // Clear out abandoned attempts
+    sqlx::query("DELETE FROM oauth_states WHERE expires_at < NOW()")
+        .execute(&state.db)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO oauth_states (state, provider, pkce_verifier, user_id, expires_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&csrf_state)
+    .bind(provider.name())
+    .bind(&request.pkce_verifier)
+    .bind(&link_user_id)
+    .bind(Utc::now() + Duration::minutes(OAUTH_STATE_TTL_MINUTES))
+    .execute(&state.db)
+    .await?;
+
+    Ok(Json(OAuthStartResponse {
+        authorize_url: request.url,
+    }))
+}
+
+async fn provider_callback(
+    State(state): State<Arc<AppState>>,
+    Path(provider_name): Path<String>,
+    client: ClientInfo,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<OAuthCallbackResponse>> {
+    let provider = identity_provider(&state, &provider_name)?;
+
+    let csrf_state = params
+        .get("state")
+        .ok_or_else(|| AppError::bad_request("Missing state parameter"))?;
+
+    let oauth_state: OAuthState = sqlx::query_as(
+        "SELECT * FROM oauth_states WHERE state = ? AND provider = ? AND expires_at > NOW()",
+    )
+    .bind(csrf_state)
+    .bind(provider.name())
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Authentication("Invalid or expired OAuth state".to_string()))?;
+
+    // States are single use; losing the race to delete it means it was replayed
+    let deleted = sqlx::query("DELETE FROM oauth_states WHERE state = ?")
+        .bind(&oauth_state.state)
+        .execute(&state.db)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(AppError::Authentication(
+            "Invalid or expired OAuth state".to_string(),
+        ));
+    }
+
+    let identity = provider
+        .complete(&params, oauth_state.pkce_verifier.as_deref())
+        .await?;
+
+    if let Some(user_id) = oauth_state.user_id {
+        let identity = identities::link(&state.db, &user_id, provider.name(), &identity).await?;
+        return Ok(Json(OAuthCallbackResponse::Linked { identity }));
+    }
+
+    match identities::find_user(&state.db, provider.name(), &identity).await? {
+        Some(user) => {
+            if !user.is_active {
+                return Err(AppError::Authentication("Account is disabled".to_string()));
+            }
+
+            identities::record_login(&state.db, provider.name(), &identity).await?;
+
+            let login = complete_login(&state, user, client).await?;
+            Ok(Json(OAuthCallbackResponse::LoggedIn(login)))
+        }
+        None => {
+            let user =
+                identities::register(&state.db, &state.passwords, provider.name(), &identity)
+                    .await?;
+            let tokens = start_session(&state, &user.id, client).await?;
+            Ok(Json(OAuthCallbackResponse::Registered(
+                LoginResponse::Authenticated { user, tokens },
+            )))
+        }
+    }
+}
+
+fn identity_provider(state: &AppState, name: &str) -> Result<Arc<dyn IdentityProvider>> {
+    state
+        .identity_providers
+        .get(name)
+        .ok_or_else(|| AppError::NotFound(format!("Identity provider {} is not enabled", name)))
+}
+
+/// Finish a login whose first factor succeeded, or ask for the second factor
+/// when the account has two-factor authentication enabled
+async fn complete_login(state: &AppState, user: User, client: ClientInfo) -> Result<LoginResponse> {
+    if mfa::is_enabled(&state.db, &user.id).await? {
+        record_login_attempt(
+            state,
+            &user.email,
+            Some(&user.id),
+            &client,
+            LoginOutcome::MfaRequired,
+        )
+        .await?;
+
+        let mfa_token = utils::generate_mfa_challenge(
+            &user.id,
+            &state.signing_keys,
+            state.config.mfa_challenge_ttl,
+        )
+        .await?;
+
+        return Ok(LoginResponse::MfaRequired(MfaChallenge {
+            mfa_required: true,
+            mfa_token,
+            expires_in: state.config.mfa_challenge_ttl.num_seconds(),
+        }));
+    }
+
+    let tokens = establish_login(state, &user, client).await?;
+
+    Ok(LoginResponse::Authenticated { user, tokens })
+}
+
+/// Record the login and start a session for a fully authenticated user
+async fn establish_login(state: &AppState, user: &User, client: ClientInfo) -> Result<AuthTokens> {
+    record_login_attempt(
+        state,
+        &user.email,
+        Some(&user.id),
+        &client,
+        LoginOutcome::Success,
+    )
+    .await?;
+
     sqlx::query("UPDATE users SET last_login = NOW() WHERE id = ?")
         .bind(&user.id)
         .execute(&state.db)
         .await?;
 
-    // Generate JWT
-    let token = utils::generate_jwt(&user.id, &state.jwt_secret)?;
+    start_session(state, &user.id, client).await
+}
+
+/// Refuse the attempt while the account or client IP is backing off, auditing the refusal
+pub(crate) async fn check_login_throttle(
+    state: &AppState,
+    email: &str,
+    client: &ClientInfo,
+) -> Result<()> {
+    let result = login_attempts::check(
+        &state.db,
+        &state.config.login_throttle,
+        email,
+        client.ip_address.as_deref(),
+    )
+    .await;
+
+    if let Err(AppError::TooManyRequests { .. }) = &result {
+        tracing::warn!(email = %email, ip = ?client.ip_address, "Login refused, too many failed attempts");
+        record_login_attempt(state, email, None, client, LoginOutcome::Locked).await?;
+    }
+
+    result
+}
+
+pub(crate) async fn record_login_attempt(
+    state: &AppState,
+    email: &str,
+    user_id: Option<&str>,
+    client: &ClientInfo,
+    outcome: LoginOutcome,
+) -> Result<()> {
+    login_attempts::record(
+        &state.db,
+        email,
+        user_id,
+        client.user_agent.as_deref(),
+        client.ip_address.as_deref(),
+        outcome,
+    )
+    .await
+}
+
+/// Create a server-side session and issue an access/refresh token pair for it
+pub(crate) async fn start_session(
+    state: &AppState,
+    user_id: &str,
+    client: ClientInfo,
+) -> Result<AuthTokens> {
+    let (session, refresh_token) = sessions::create_session(
+        &state.db,
+        user_id,
+        client.user_agent,
+        client.ip_address,
+        state.config.refresh_token_ttl,
+    )
+    .await?;
+
+    let roles = roles::user_roles(&state.db, user_id).await?;
+
+    let token = utils::generate_jwt(
+        user_id,
+        &session.id,
+        roles,
+        &state.signing_keys,
+        state.config.access_token_ttl,
+    )
+    .await?;
 
-    Ok(Json(LoginResponse { user, token }))
+    Ok(AuthTokens {
+        token,
+        refresh_token,
+        expires_in: state.config.access_token_ttl.num_seconds(),
+    })
 }
@@ -1,12 +1,27 @@
-use axum::{routing::get, Router};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
 use std::sync::Arc;
 
-use crate::AppState;
+use crate::{
+    api::{extractors::FreshTotp, middleware::auth::AuthUser},
+    models::{ClanInvite, ClanMember, InviteMemberRequest, TransferLeadershipRequest},
+    services::clans,
+    utils::errors::Result,
+    AppState,
+};
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(get_clans))
         .route("/my", get(get_my_clan))
+        .route("/:id/invites", post(invite_member))
+        .route("/invites/:id/accept", post(accept_invite))
+        .route("/invites/:id", delete(decline_invite))
+        .route("/:id/transfer-leadership", post(transfer_leadership))
 }
 
 async fn get_clans() -> &'static str {
@@ -16,3 +31,54 @@ async fn get_clans() -> &'static str {
 async fn get_my_clan() -> &'static str {
     "Get user's clan endpoint"
 }
+
+async fn invite_member(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(clan_id): Path<String>,
+    Json(payload): Json<InviteMemberRequest>,
+) -> Result<(StatusCode, Json<ClanInvite>)> {
+    let invite = clans::invite_member(&state.db, &clan_id, &auth.user_id, &payload.user_id).await?;
+
+    Ok((StatusCode::CREATED, Json(invite)))
+}
+
+async fn accept_invite(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(invite_id): Path<String>,
+) -> Result<Json<ClanMember>> {
+    let member = clans::accept_invite(&state.db, &auth.user_id, &invite_id).await?;
+
+    Ok(Json(member))
+}
+
+async fn decline_invite(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(invite_id): Path<String>,
+) -> Result<StatusCode> {
+    clans::decline_invite(&state.db, &auth.user_id, &invite_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Hand clan leadership to another member; returns the new leader's membership
+async fn transfer_leadership(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    _fresh_totp: FreshTotp,
+    Path(clan_id): Path<String>,
+    Json(payload): Json<TransferLeadershipRequest>,
+) -> Result<Json<ClanMember>> {
+    let member =
+        clans::transfer_leadership(&state.db, &clan_id, &auth.user_id, &payload.user_id).await?;
+    tracing::info!(
+        clan_id = %clan_id,
+        from = %auth.user_id,
+        to = %payload.user_id,
+        "Clan leadership transferred"
+    );
+
+    Ok(Json(member))
+}
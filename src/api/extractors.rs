@@ -0,0 +1,127 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::{header, request::Parts},
+};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use crate::{
+    api::{auth, middleware::auth::AuthUser},
+    services::{login_attempts::LoginOutcome, mfa},
+    utils::AppError,
+    AppState,
+};
+
+/// Header carrying a fresh TOTP code for high-value actions
+pub const TOTP_CODE_HEADER: &str = "X-TOTP-Code";
+
+/// Details about the calling client captured for sessions and auditing
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    Arc<AppState>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = Arc::<AppState>::from_ref(state);
+
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(500).collect());
+
+        // Only trust forwarded headers when running behind a known proxy
+        let forwarded_ip = if state.config.trust_proxy_headers {
+            parts
+                .headers
+                .get("X-Forwarded-For")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        } else {
+            None
+        };
+
+        let ip_address = forwarded_ip.or_else(|| {
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        });
+
+        Ok(ClientInfo {
+            user_agent,
+            ip_address,
+        })
+    }
+}
+
+/// Guard for high-value actions (trade acceptance, clan leadership transfer).
+///
+/// When `REQUIRE_TOTP_FOR_HIGH_VALUE` is on, users with two-factor authentication
+/// enabled must send a current code in the `X-TOTP-Code` header. Recovery codes
+/// are not accepted here.
+#[derive(Debug, Clone, Copy)]
+pub struct FreshTotp;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for FreshTotp
+where
+    Arc<AppState>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let app_state = Arc::<AppState>::from_ref(state);
+
+        if !app_state.config.require_totp_for_high_value {
+            return Ok(FreshTotp);
+        }
+
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+
+        if !mfa::is_enabled(&app_state.db, &auth_user.user_id).await? {
+            return Ok(FreshTotp);
+        }
+
+        let Ok(client) = ClientInfo::from_request_parts(parts, state).await;
+        let code = parts
+            .headers
+            .get(TOTP_CODE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| AppError::forbidden("A current two-factor code is required"))?;
+
+        let (email,): (String,) = sqlx::query_as("SELECT email FROM users WHERE id = ?")
+            .bind(&auth_user.user_id)
+            .fetch_one(&app_state.db)
+            .await?;
+
+        // Guesses share the login backoff so the header can't be brute forced
+        auth::check_login_throttle(&app_state, &email, &client).await?;
+
+        if !mfa::verify_totp(&app_state.db, &auth_user.user_id, code).await? {
+            auth::record_login_attempt(
+                &app_state,
+                &email,
+                Some(&auth_user.user_id),
+                &client,
+                LoginOutcome::InvalidMfaCode,
+            )
+            .await?;
+            return Err(AppError::forbidden("Invalid two-factor code"));
+        }
+
+        Ok(FreshTotp)
+    }
+}
@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    api::middleware::{auth::AuthClient, permissions::RequirePermission},
+    models::{CreateGameRequest, CreateLinkCodeRequest, Game, LinkCodeResponse, UserGame},
+    services::{game_clients, games},
+    utils::{errors::Result, AppError},
+    AppState,
+};
+
+/// The game catalog, and game server endpoints for linking and syncing players' game accounts
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(get_games))
+        .route(
+            "/",
+            post(create_game).route_layer(RequirePermission("games:manage")),
+        )
+        .route(
+            "/link-codes",
+            post(create_link_code).route_layer(RequirePermission("games:link")),
+        )
+        .route(
+            "/:game_name/players/:game_user_id/sync",
+            post(sync_player).route_layer(RequirePermission("games:sync")),
+        )
+}
+
+async fn get_games(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Game>>> {
+    Ok(Json(games::list_games(&state.db).await?))
+}
+
+async fn create_game(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateGameRequest>,
+) -> Result<(StatusCode, Json<Game>)> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let game = games::create_game(&state.db, &payload).await?;
+
+    Ok((StatusCode::CREATED, Json(game)))
+}
+
+/// Issue a link code for a player to enter at `POST /users/me/games`
+async fn create_link_code(
+    State(state): State<Arc<AppState>>,
+    client: AuthClient,
+    Json(payload): Json<CreateLinkCodeRequest>,
+) -> Result<(StatusCode, Json<LinkCodeResponse>)> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    ensure_client_allows_game(&client, &payload.game_name)?;
+
+    let link_code = games::issue_link_code(
+        &state.db,
+        &client.client_id,
+        &payload.game_name,
+        &payload.game_user_id,
+        state.config.game_link_code_ttl,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(link_code)))
+}
+
+/// Mark a player as synced and return the Bridge account their game account is linked to
+async fn sync_player(
+    State(state): State<Arc<AppState>>,
+    client: AuthClient,
+    Path((game_name, game_user_id)): Path<(String, String)>,
+) -> Result<Json<UserGame>> {
+    ensure_client_allows_game(&client, &game_name)?;
+
+    let game = games::sync_game_account(&state.db, &game_name, &game_user_id).await?;
+
+    Ok(Json(game))
+}
+
+fn ensure_client_allows_game(client: &AuthClient, game_name: &str) -> Result<()> {
+    if !game_clients::allows_game(&client.allowed_games, game_name) {
+        return Err(AppError::forbidden(
+            "This API key can't act for players of this game",
+        ));
+    }
+
+    Ok(())
+}
@@ -1,18 +1,178 @@
-use axum::{routing::get, Router};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    middleware as axum_middleware,
+    routing::{get, post, put},
+    Json, Router,
+};
 use std::sync::Arc;
 
-use crate::AppState;
+use crate::{
+    api::middleware::{
+        auth::{AuthClient, AuthUser},
+        permissions::RequirePermission,
+        signature::require_signed_client,
+    },
+    models::{
+        CreateItemRequest, GrantItemRequest, InventoryItem, ItemFilter, ItemGameRule,
+        SetItemGameRuleRequest, UserInventory,
+    },
+    services::{game_clients, inventory},
+    utils::{errors::Result, AppError},
+    AppState,
+};
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(get_inventory))
         .route("/items", get(get_items))
+        .route(
+            "/items",
+            post(create_item).route_layer(RequirePermission("items:create")),
+        )
+        .route("/items/:id/games", get(get_item_game_rules))
+        .route(
+            "/items/:id/games/:game_name",
+            put(set_item_game_rule)
+                .delete(remove_item_game_rule)
+                .route_layer(RequirePermission("items:create")),
+        )
+        .route(
+            "/users/:user_id",
+            get(get_user_inventory).route_layer(RequirePermission("inventory:read")),
+        )
+        .route(
+            "/grant",
+            post(grant_item)
+                .route_layer(RequirePermission("items:grant"))
+                .route_layer(axum_middleware::from_fn(require_signed_client)),
+        )
 }
 
-async fn get_inventory() -> &'static str {
-    "Get user inventory endpoint"
+/// The caller's items, optionally filtered by game and eligibility
+async fn get_inventory(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Query(filter): Query<ItemFilter>,
+) -> Result<Json<Vec<UserInventory>>> {
+    let items = inventory::list_user_items(&state.db, &auth.user_id, &filter).await?;
+
+    Ok(Json(items))
+}
+
+/// The item catalog, optionally filtered by game and eligibility
+async fn get_items(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<ItemFilter>,
+) -> Result<Json<Vec<InventoryItem>>> {
+    let items = inventory::list_items(&state.db, &filter).await?;
+
+    Ok(Json(items))
+}
+
+async fn create_item(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateItemRequest>,
+) -> Result<(StatusCode, Json<InventoryItem>)> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let item = inventory::create_item(&state.db, &payload).await?;
+
+    Ok((StatusCode::CREATED, Json(item)))
 }
 
-async fn get_items() -> &'static str {
-    "Get available items endpoint"
+async fn get_item_game_rules(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<i32>,
+) -> Result<Json<Vec<ItemGameRule>>> {
+    let rules = inventory::list_item_game_rules(&state.db, item_id).await?;
+
+    Ok(Json(rules))
+}
+
+async fn set_item_game_rule(
+    State(state): State<Arc<AppState>>,
+    Path((item_id, game_name)): Path<(i32, String)>,
+    Json(payload): Json<SetItemGameRuleRequest>,
+) -> Result<Json<ItemGameRule>> {
+    let rule = inventory::set_item_game_rule(&state.db, item_id, &game_name, &payload).await?;
+
+    Ok(Json(rule))
+}
+
+async fn remove_item_game_rule(
+    State(state): State<Arc<AppState>>,
+    Path((item_id, game_name)): Path<(i32, String)>,
+) -> Result<StatusCode> {
+    if !inventory::remove_item_game_rule(&state.db, item_id, &game_name).await? {
+        return Err(AppError::not_found("Item game rule"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A player's items, for admins and game servers
+async fn get_user_inventory(
+    State(state): State<Arc<AppState>>,
+    client: Option<AuthClient>,
+    Path(user_id): Path<String>,
+    Query(filter): Query<ItemFilter>,
+) -> Result<Json<Vec<UserInventory>>> {
+    if let Some(client) = client {
+        ensure_client_can_act_for(&state, &client, &user_id).await?;
+    }
+
+    let items = inventory::list_user_items(&state.db, &user_id, &filter).await?;
+
+    Ok(Json(items))
+}
+
+async fn grant_item(
+    State(state): State<Arc<AppState>>,
+    client: Option<AuthClient>,
+    Json(payload): Json<GrantItemRequest>,
+) -> Result<(StatusCode, Json<UserInventory>)> {
+    if let Some(client) = client {
+        ensure_client_can_act_for(&state, &client, &payload.user_id).await?;
+        ensure_client_can_grant(&state, &client, payload.item_id).await?;
+    }
+
+    let user_item = inventory::grant_item(&state.db, &payload).await?;
+
+    Ok((StatusCode::CREATED, Json(user_item)))
+}
+
+/// Game servers may only act for players who play one of their allowed games
+async fn ensure_client_can_act_for(
+    state: &AppState,
+    client: &AuthClient,
+    user_id: &str,
+) -> Result<()> {
+    if !game_clients::can_act_for(&state.db, &client.allowed_games, user_id).await? {
+        return Err(AppError::forbidden(
+            "This API key can't act for players outside its allowed games",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Game servers may only grant items from their allowed games; Bridge-wide
+/// items need a key allowed any game
+async fn ensure_client_can_grant(
+    state: &AppState,
+    client: &AuthClient,
+    item_id: i32,
+) -> Result<()> {
+    let game = inventory::item_game(&state.db, item_id).await?;
+    if !game_clients::allows_item_game(&client.allowed_games, game.as_deref()) {
+        return Err(AppError::forbidden(
+            "This API key can't grant items from outside its allowed games",
+        ));
+    }
+
+    Ok(())
 }
@@ -1,18 +1,59 @@
-use axum::{routing::get, Router};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
 use std::sync::Arc;
+use validator::Validate;
 
-use crate::AppState;
+use crate::{
+    api::middleware::auth::AuthUser,
+    models::{AuctionBid, MarketplaceFilter, MarketplaceListingsResponse, PlaceBidRequest},
+    services::marketplace,
+    utils::{errors::Result, AppError},
+    AppState,
+};
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(get_listings))
         .route("/my", get(get_my_listings))
+        .route("/:id/bids", post(place_bid))
 }
 
-async fn get_listings() -> &'static str {
-    "Get marketplace listings endpoint"
+async fn get_listings(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Query(filter): Query<MarketplaceFilter>,
+) -> Result<Json<MarketplaceListingsResponse>> {
+    let listings = marketplace::list_listings(&state.db, &auth.user_id, &filter, false).await?;
+
+    Ok(Json(listings))
+}
+
+async fn get_my_listings(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Query(filter): Query<MarketplaceFilter>,
+) -> Result<Json<MarketplaceListingsResponse>> {
+    let listings = marketplace::list_listings(&state.db, &auth.user_id, &filter, true).await?;
+
+    Ok(Json(listings))
 }
 
-async fn get_my_listings() -> &'static str {
-    "Get user's listings endpoint"
+async fn place_bid(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(listing_id): Path<String>,
+    Json(payload): Json<PlaceBidRequest>,
+) -> Result<(StatusCode, Json<AuctionBid>)> {
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let bid =
+        marketplace::place_bid(&state.db, &auth.user_id, &listing_id, payload.bid_amount).await?;
+
+    Ok((StatusCode::CREATED, Json(bid)))
 }
@@ -1,11 +1,17 @@
-use axum::{routing::get, Router};
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
 use std::sync::Arc;
 
-use crate::AppState;
+use crate::{
+    api::middleware::auth::AuthUser,
+    models::{CreateMessageRequest, Message},
+    services::blocks::{self, Interaction},
+    utils::{errors::Result, AppError},
+    AppState,
+};
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/", get(get_messages))
+        .route("/", get(get_messages).post(send_message))
         .route("/unread", get(get_unread_count))
 }
 
@@ -16,3 +22,58 @@ async fn get_messages() -> &'static str {
 async fn get_unread_count() -> &'static str {
     "Get unread message count endpoint"
 }
+
+async fn send_message(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<CreateMessageRequest>,
+) -> Result<(StatusCode, Json<Message>)> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    if payload.recipient_id == auth.user_id {
+        return Err(AppError::bad_request("You can't message yourself"));
+    }
+
+    let recipient: Option<(String,)> = sqlx::query_as(
+        "SELECT id FROM users WHERE id = ? AND is_active = true AND deleted_at IS NULL",
+    )
+    .bind(&payload.recipient_id)
+    .fetch_optional(&state.db)
+    .await?;
+    if recipient.is_none() {
+        return Err(AppError::not_found("User"));
+    }
+
+    blocks::ensure_can_interact(
+        &state.db,
+        &auth.user_id,
+        &payload.recipient_id,
+        Interaction::Message,
+    )
+    .await?;
+
+    let message = Message::new(
+        auth.user_id,
+        payload.recipient_id,
+        payload.subject,
+        payload.content,
+    );
+
+    sqlx::query(
+        "INSERT INTO messages (id, sender_id, recipient_id, subject, content, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&message.id)
+    .bind(&message.sender_id)
+    .bind(&message.recipient_id)
+    .bind(&message.subject)
+    .bind(&message.content)
+    .bind(message.created_at)
+    .execute(&state.db)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(message)))
+}
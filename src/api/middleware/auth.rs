@@ -1,55 +1,181 @@
 use axum::http::HeaderMap;
 use axum::{
-    extract::{Request, State},
-    http::StatusCode,
+    async_trait,
+    extract::{FromRef, FromRequestParts, Request, State},
+    http::request::Parts,
     middleware::Next,
     response::Response,
 };
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-use crate::{utils, AppState};
+use crate::{
+    api::middleware::signature,
+    services::{game_clients, roles, sessions, social},
+    utils::{self, AppError},
+    AppState,
+};
+
+/// Header carrying a game server's API key
+pub const API_KEY_HEADER: &str = "X-Bridge-Key";
 
 #[derive(Clone)]
 pub struct AuthUser {
     pub user_id: String,
+    pub session_id: String,
+    /// Roles carried by the access token
+    pub roles: Vec<String>,
+    /// Permissions granted by those roles that the user still holds
+    pub permissions: HashSet<String>,
+}
+
+impl AuthUser {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.contains(permission)
+    }
+}
+
+/// A game server authenticated with an API key, acting on behalf of players
+#[derive(Clone)]
+pub struct AuthClient {
+    pub client_id: String,
+    pub name: String,
+    pub scopes: HashSet<String>,
+    pub allowed_games: Vec<String>,
+    /// Authenticated by an HMAC signature rather than a bare API key
+    pub signed: bool,
+}
+
+impl AuthClient {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.contains(scope)
+    }
 }
 
+/// Authenticate every request to the protected routers, with a player token,
+/// a signed game server request or a game server API key
 pub async fn auth_middleware(
     State(state): State<Arc<AppState>>,
     mut request: Request,
     next: Next,
-) -> Result<Response, StatusCode> {
-    let headers = request.headers();
+) -> Result<Response, AppError> {
+    // Game servers authenticate with a request signature or an API key instead of a player token
+    if signature::is_signed(request.headers()) {
+        let (mut request, client) = signature::authenticate_signed(&state, request).await?;
+
+        request.extensions_mut().insert(client);
+
+        return Ok(next.run(request).await);
+    }
+
+    if let Some(api_key) = request.headers().get(API_KEY_HEADER) {
+        let api_key = api_key
+            .to_str()
+            .map_err(|_| AppError::Authentication("Invalid API key".to_string()))?;
+        let client = authenticate_client(&state, api_key).await?;
+
+        request.extensions_mut().insert(client);
 
-    // Skip auth for auth routes
-    if request.uri().path().starts_with("/api/v1/auth") {
         return Ok(next.run(request).await);
     }
 
+    let auth_user = authenticate(&state, request.headers()).await?;
+
+    // Add user info to request extensions
+    request.extensions_mut().insert(auth_user);
+
+    Ok(next.run(request).await)
+}
+
+/// Resolve the bearer token in `headers` to a user with a live session
+pub async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<AuthUser, AppError> {
     // Extract token from Authorization header
-    let token = extract_token(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let token = extract_token(headers)
+        .ok_or_else(|| AppError::Authentication("Missing bearer token".to_string()))?;
 
     // Validate token
-    let claims =
-        utils::validate_jwt(&token, &state.jwt_secret).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let claims = utils::validate_jwt(&token, &state.signing_keys).await?;
 
-    // Add user info to request extensions
-    request.extensions_mut().insert(AuthUser {
+    // Reject tokens whose session has been revoked or has expired
+    if !sessions::is_session_active(&state.db, &claims.sid, &claims.sub).await? {
+        return Err(AppError::Authentication(
+            "Session has expired or been revoked".to_string(),
+        ));
+    }
+
+    social::touch_last_seen(&state.db, &claims.sub).await?;
+
+    // Most users hold no roles, so only privileged tokens cost a lookup
+    let permissions = roles::permissions_for(&state.db, &claims.sub, &claims.roles).await?;
+
+    Ok(AuthUser {
         user_id: claims.sub,
-    });
+        session_id: claims.sid,
+        roles: claims.roles,
+        permissions,
+    })
+}
 
-    Ok(next.run(request).await)
+/// Resolve an API key to an active game client
+pub async fn authenticate_client(state: &AppState, api_key: &str) -> Result<AuthClient, AppError> {
+    let client = game_clients::authenticate(&state.db, api_key).await?;
+
+    Ok(AuthClient {
+        scopes: client.scope_list().into_iter().collect(),
+        allowed_games: client.allowed_game_list(),
+        client_id: client.id,
+        name: client.name,
+        signed: false,
+    })
 }
 
 fn extract_token(headers: &HeaderMap) -> Option<String> {
     headers
         .get("Authorization")
         .and_then(|value| value.to_str().ok())
-        .and_then(|value| {
-            if value.starts_with("Bearer ") {
-                Some(value[7..].to_string())
-            } else {
-                None
-            }
-        })
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.to_string())
+}
+
+/// The signed-in player. Reuses the user resolved by `auth_middleware` on protected
+/// routes and authenticates the bearer token itself on public ones. Handlers taking
+/// `AuthUser` only serve players; API key requests are turned away.
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    Arc<AppState>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(auth_user) = parts.extensions.get::<AuthUser>() {
+            return Ok(auth_user.clone());
+        }
+
+        if parts.extensions.get::<AuthClient>().is_some() {
+            return Err(AppError::forbidden("This endpoint requires a user token"));
+        }
+
+        let state = Arc::<AppState>::from_ref(state);
+        let auth_user = authenticate(&state, &parts.headers).await?;
+        parts.extensions.insert(auth_user.clone());
+
+        Ok(auth_user)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthClient
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthClient>()
+            .cloned()
+            .ok_or_else(|| AppError::Authentication("API key required".to_string()))
+    }
 }
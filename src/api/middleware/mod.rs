@@ -1 +1,4 @@
 pub mod auth;
+pub mod permissions;
+pub mod signature;
+pub mod verified;
@@ -0,0 +1,96 @@
+use axum::{
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+use crate::{
+    api::middleware::auth::{AuthClient, AuthUser},
+    utils::AppError,
+};
+
+/// Route layer that only lets through users holding a permission, or game clients
+/// whose API key has the scope of the same name, e.g.
+/// `.route_layer(RequirePermission("items:grant"))`. Must run after `auth_middleware`.
+#[derive(Debug, Clone, Copy)]
+pub struct RequirePermission(pub &'static str);
+
+impl<S> Layer<S> for RequirePermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            permission: self.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequirePermissionService<S> {
+    inner: S,
+    permission: &'static str,
+}
+
+impl<S> Service<Request> for RequirePermissionService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let extensions = request.extensions();
+        let rejection = match (extensions.get::<AuthUser>(), extensions.get::<AuthClient>()) {
+            (Some(auth_user), _) if auth_user.has_permission(self.permission) => None,
+            (_, Some(client)) if client.has_scope(self.permission) => None,
+            (Some(auth_user), _) => {
+                tracing::warn!(
+                    user_id = %auth_user.user_id,
+                    permission = %self.permission,
+                    path = %request.uri().path(),
+                    "Permission denied"
+                );
+                Some(AppError::Forbidden(format!(
+                    "Missing permission '{}'",
+                    self.permission
+                )))
+            }
+            (_, Some(client)) => {
+                tracing::warn!(
+                    client_id = %client.client_id,
+                    scope = %self.permission,
+                    path = %request.uri().path(),
+                    "API key scope denied"
+                );
+                Some(AppError::Forbidden(format!(
+                    "API key is missing scope '{}'",
+                    self.permission
+                )))
+            }
+            (None, None) => Some(AppError::Authentication(
+                "Authentication required".to_string(),
+            )),
+        };
+
+        if let Some(error) = rejection {
+            return Box::pin(async move { Ok(error.into_response()) });
+        }
+
+        // The clone may not be ready, so call the instance poll_ready was called on
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { inner.call(request).await })
+    }
+}
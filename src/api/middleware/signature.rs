@@ -0,0 +1,247 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{OriginalUri, Request},
+    http::{HeaderMap, Method},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use ring::hmac;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    api::middleware::auth::AuthClient,
+    services::game_clients,
+    utils::{errors::Result, AppError},
+    AppState,
+};
+
+/// ID of the game client whose secret signed the request
+pub const KEY_ID_HEADER: &str = "X-Bridge-Key-Id";
+/// Unix timestamp in seconds
+pub const TIMESTAMP_HEADER: &str = "X-Bridge-Timestamp";
+/// Random value, unique per request
+pub const NONCE_HEADER: &str = "X-Bridge-Nonce";
+/// Hex SHA-256 of the request body (of the empty string when there is none)
+pub const CONTENT_HASH_HEADER: &str = "X-Bridge-Content-SHA256";
+/// Hex HMAC-SHA256 of the canonical request, keyed with the client's signing secret
+pub const SIGNATURE_HEADER: &str = "X-Bridge-Signature";
+
+/// Larger bodies are rejected rather than buffered for hashing
+const MAX_SIGNED_BODY_BYTES: usize = 1024 * 1024;
+const MIN_NONCE_LENGTH: usize = 16;
+const MAX_NONCE_LENGTH: usize = 128;
+
+/// Whether the request carries a signature and should be authenticated by it
+pub fn is_signed(headers: &HeaderMap) -> bool {
+    headers.contains_key(SIGNATURE_HEADER)
+}
+
+/// The string a client signs: method, path with query, timestamp, nonce and body
+/// hash, separated by newlines
+pub fn canonical_request(
+    method: &Method,
+    path_and_query: &str,
+    timestamp: &str,
+    nonce: &str,
+    content_hash: &str,
+) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}",
+        method.as_str(),
+        path_and_query,
+        timestamp,
+        nonce,
+        content_hash
+    )
+}
+
+/// Hex HMAC-SHA256 of `canonical` keyed with `secret`
+pub fn sign(secret: &str, canonical: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hex::encode(hmac::sign(&key, canonical.as_bytes()).as_ref())
+}
+
+/// Whether `signed_at` is at most `max_skew` seconds from `now`. The timestamp
+/// comes from the client, so any `i64` has to be handled without overflowing.
+fn within_skew(signed_at: i64, now: i64, max_skew: i64) -> bool {
+    u64::try_from(max_skew).is_ok_and(|max_skew| now.abs_diff(signed_at) <= max_skew)
+}
+
+/// Verify a signed request and resolve it to its game client. The body is
+/// buffered to check its hash, so the request is handed back rebuilt.
+pub async fn authenticate_signed(
+    state: &AppState,
+    request: Request,
+) -> Result<(Request, AuthClient)> {
+    let (parts, body) = request.into_parts();
+    let headers = &parts.headers;
+
+    let key_id = header(headers, KEY_ID_HEADER)?;
+    let timestamp = header(headers, TIMESTAMP_HEADER)?;
+    let nonce = header(headers, NONCE_HEADER)?;
+    let content_hash = header(headers, CONTENT_HASH_HEADER)?;
+    let signature = header(headers, SIGNATURE_HEADER)?;
+
+    let signed_at: i64 = timestamp
+        .parse()
+        .map_err(|_| AppError::Authentication("Invalid request timestamp".to_string()))?;
+    let max_skew = state.config.signature_max_skew.num_seconds();
+    if !within_skew(signed_at, Utc::now().timestamp(), max_skew) {
+        return Err(AppError::Authentication(
+            "Request timestamp is outside the allowed window".to_string(),
+        ));
+    }
+
+    if !(MIN_NONCE_LENGTH..=MAX_NONCE_LENGTH).contains(&nonce.len()) {
+        return Err(AppError::Authentication(
+            "Invalid request nonce".to_string(),
+        ));
+    }
+
+    let body = to_bytes(body, MAX_SIGNED_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::bad_request("Request body is too large to sign"))?;
+    if hex::encode(Sha256::digest(&body)) != content_hash.to_ascii_lowercase() {
+        return Err(AppError::Authentication(
+            "Body does not match its content hash".to_string(),
+        ));
+    }
+
+    let client = game_clients::find_active_client(&state.db, key_id).await?;
+    let secret = client.signing_secret.as_deref().ok_or_else(|| {
+        AppError::Authentication("Request signing is not set up for this key".to_string())
+    })?;
+
+    // Sign what the client sent, before nesting stripped the path prefix
+    let path_and_query = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map(|OriginalUri(uri)| uri)
+        .unwrap_or(&parts.uri)
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+    let canonical = canonical_request(
+        &parts.method,
+        path_and_query,
+        timestamp,
+        nonce,
+        content_hash,
+    );
+
+    let signature = hex::decode(signature)
+        .map_err(|_| AppError::Authentication("Invalid request signature".to_string()))?;
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::verify(&key, canonical.as_bytes(), &signature)
+        .map_err(|_| AppError::Authentication("Invalid request signature".to_string()))?;
+
+    // Only remember nonces of genuine requests, so they can't be used to fill the cache
+    if !state.nonces.insert(
+        format!("{}:{}", client.id, nonce),
+        signed_at.saturating_add(max_skew),
+    ) {
+        tracing::warn!(client_id = %client.id, "Replayed signed request rejected");
+        return Err(AppError::Authentication(
+            "Request nonce has already been used".to_string(),
+        ));
+    }
+
+    let auth_client = AuthClient {
+        scopes: client.scope_list().into_iter().collect(),
+        allowed_games: client.allowed_game_list(),
+        client_id: client.id,
+        name: client.name,
+        signed: true,
+    };
+
+    Ok((Request::from_parts(parts, Body::from(body)), auth_client))
+}
+
+/// Require game clients to sign their requests on sensitive routes such as item
+/// grants and trade settlement. Player requests pass through untouched.
+pub async fn require_signed_client(request: Request, next: Next) -> Result<Response> {
+    match request.extensions().get::<AuthClient>() {
+        Some(client) if !client.signed => Err(AppError::Authentication(
+            "This endpoint requires a signed request".to_string(),
+        )),
+        _ => Ok(next.run(request).await),
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| AppError::Authentication(format!("Missing {} header", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_request_joins_fields_with_newlines() {
+        let canonical = canonical_request(
+            &Method::POST,
+            "/api/v1/inventory/grant?dry_run=true",
+            "1700000000",
+            "0123456789abcdef",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        );
+
+        assert_eq!(
+            canonical,
+            "POST\n/api/v1/inventory/grant?dry_run=true\n1700000000\n0123456789abcdef\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn sign_matches_rfc_4231_vector() {
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn signature_covers_every_field() {
+        let base = canonical_request(&Method::POST, "/a", "1", "nonce", "hash");
+        let variants = [
+            canonical_request(&Method::PUT, "/a", "1", "nonce", "hash"),
+            canonical_request(&Method::POST, "/b", "1", "nonce", "hash"),
+            canonical_request(&Method::POST, "/a", "2", "nonce", "hash"),
+            canonical_request(&Method::POST, "/a", "1", "other", "hash"),
+            canonical_request(&Method::POST, "/a", "1", "nonce", "other"),
+        ];
+
+        for variant in variants {
+            assert_ne!(sign("secret", &base), sign("secret", &variant));
+        }
+        assert_ne!(sign("secret", &base), sign("other", &base));
+    }
+
+    #[test]
+    fn accepts_timestamps_inside_the_skew() {
+        assert!(within_skew(1_000, 1_000, 300));
+        assert!(within_skew(700, 1_000, 300));
+        assert!(within_skew(1_300, 1_000, 300));
+    }
+
+    #[test]
+    fn rejects_timestamps_outside_the_skew() {
+        assert!(!within_skew(699, 1_000, 300));
+        assert!(!within_skew(1_301, 1_000, 300));
+        assert!(!within_skew(1_000, 1_000, -1));
+    }
+
+    #[test]
+    fn extreme_timestamps_do_not_overflow() {
+        let now = Utc::now().timestamp();
+
+        assert!(!within_skew(i64::MIN, now, 300));
+        assert!(!within_skew(i64::MAX, now, 300));
+        assert!(!within_skew(i64::MIN, i64::MAX, i64::MAX));
+    }
+}
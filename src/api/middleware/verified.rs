@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::{
+    api::middleware::auth::AuthUser,
+    utils::{errors::Result, AppError},
+    AppState,
+};
+
+/// Reject state-changing requests from users who haven't verified their email,
+/// when `REQUIRE_VERIFIED_EMAIL` is enabled. Read-only requests and game server
+/// calls are always allowed.
+pub async fn require_verified_email(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<AuthUser>,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    let Some(auth_user) = auth_user else {
+        return Ok(next.run(request).await);
+    };
+
+    if !state.config.require_verified_email || request.method().is_safe() {
+        return Ok(next.run(request).await);
+    }
+
+    let verified: Option<(Option<DateTime<Utc>>,)> =
+        sqlx::query_as("SELECT email_verified_at FROM users WHERE id = ?")
+            .bind(&auth_user.user_id)
+            .fetch_optional(&state.db)
+            .await?;
+
+    match verified {
+        Some((Some(_),)) => Ok(next.run(request).await),
+        _ => Err(AppError::forbidden(
+            "Verify your email address before trading",
+        )),
+    }
+}
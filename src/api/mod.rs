@@ -1,11 +1,16 @@
-mod auth;
+mod admin;
+pub(crate) mod auth;
 mod clans;
+pub mod extractors;
+mod games;
 mod inventory;
 mod marketplace;
 mod messages;
-mod middleware;
+pub mod middleware;
+mod social;
 mod trading;
 mod users;
+mod well_known;
 
 use axum::{middleware as axum_middleware, Router};
 use std::sync::Arc;
@@ -13,20 +18,42 @@ use std::sync::Arc;
 use crate::AppState;
 
 pub fn routes(state: Arc<AppState>) -> Router {
-    Router::new()
-        // Public routes
-        .nest("/auth", auth::routes())
-        // Protected routes
-        .nest("/users", users::routes())
+    // Trading and marketplace actions require a verified email
+    let verified_email = axum_middleware::from_fn_with_state(
+        state.clone(),
+        middleware::verified::require_verified_email,
+    );
+
+    // Public routes; handlers that accept an optional or explicit token authenticate it themselves
+    let public = Router::new().nest("/auth", auth::routes());
+
+    // Protected routes
+    let protected = Router::new()
+        .nest("/users", users::routes().merge(social::routes()))
         .nest("/inventory", inventory::routes())
-        .nest("/trading", trading::routes())
+        .nest(
+            "/trading",
+            trading::routes().route_layer(verified_email.clone()),
+        )
         .nest("/clans", clans::routes())
-        .nest("/marketplace", marketplace::routes())
+        .nest("/games", games::routes())
+        .nest(
+            "/marketplace",
+            marketplace::routes().route_layer(verified_email),
+        )
         .nest("/messages", messages::routes())
-        // Add authentication middleware to protected routes
-        .layer(axum_middleware::from_fn_with_state(
+        .nest("/admin", admin::routes())
+        .route_layer(axum_middleware::from_fn_with_state(
             state.clone(),
             middleware::auth::auth_middleware,
-        ))
+        ));
+
+    public.merge(protected).with_state(state)
+}
+
+/// Unauthenticated discovery documents served from `/.well-known`
+pub fn well_known_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .nest("/.well-known", well_known::routes())
         .with_state(state)
 }
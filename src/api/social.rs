@@ -0,0 +1,204 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    api::middleware::auth::AuthUser,
+    models::{
+        BlockUserRequest, BlockedUser, Friend, FriendRequest, FriendRequestInbox,
+        SendFriendRequest, TradeSettings, TrustTraderRequest, TrustedTrader,
+    },
+    services::{blocks, social, trading},
+    utils::{errors::Result, AppError},
+    AppState,
+};
+
+/// Friends, blocks and trading relationships of the signed-in user, merged into `/users`
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/me/friends", get(list_friends))
+        .route("/me/friends/:user_id", delete(remove_friend))
+        .route(
+            "/me/friend-requests",
+            get(list_friend_requests).post(send_friend_request),
+        )
+        .route("/me/friend-requests/:id", delete(cancel_friend_request))
+        .route(
+            "/me/friend-requests/:id/accept",
+            post(accept_friend_request),
+        )
+        .route(
+            "/me/friend-requests/:id/decline",
+            post(decline_friend_request),
+        )
+        .route("/me/blocks", get(list_blocked).post(block_user))
+        .route("/me/blocks/:user_id", delete(unblock_user))
+        .route(
+            "/me/trusted-traders",
+            get(list_trusted_traders).post(trust_trader),
+        )
+        .route("/me/trusted-traders/:user_id", delete(untrust_trader))
+        .route(
+            "/me/trade-settings",
+            get(get_trade_settings).put(update_trade_settings),
+        )
+}
+
+async fn list_friends(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<Vec<Friend>>> {
+    let friends = social::list_friends(&state.db, &auth.user_id).await?;
+
+    Ok(Json(friends))
+}
+
+async fn remove_friend(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(friend_id): Path<String>,
+) -> Result<StatusCode> {
+    if !social::remove_friend(&state.db, &auth.user_id, &friend_id).await? {
+        return Err(AppError::not_found("Friend"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_friend_requests(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<FriendRequestInbox>> {
+    let inbox = social::friend_requests(&state.db, &auth.user_id).await?;
+
+    Ok(Json(inbox))
+}
+
+async fn send_friend_request(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<SendFriendRequest>,
+) -> Result<(StatusCode, Json<FriendRequest>)> {
+    let request = social::send_friend_request(&state.db, &auth.user_id, &payload.user_id).await?;
+
+    Ok((StatusCode::CREATED, Json(request)))
+}
+
+async fn accept_friend_request(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(request_id): Path<String>,
+) -> Result<StatusCode> {
+    social::accept_friend_request(&state.db, &auth.user_id, &request_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn decline_friend_request(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(request_id): Path<String>,
+) -> Result<StatusCode> {
+    social::decline_friend_request(&state.db, &auth.user_id, &request_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Withdraw a request the caller sent
+async fn cancel_friend_request(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(request_id): Path<String>,
+) -> Result<StatusCode> {
+    social::cancel_friend_request(&state.db, &auth.user_id, &request_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_blocked(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<Vec<BlockedUser>>> {
+    let blocked = blocks::list_blocked(&state.db, &auth.user_id).await?;
+
+    Ok(Json(blocked))
+}
+
+/// Block a user; they can no longer message, trade with, befriend or find the caller
+async fn block_user(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<BlockUserRequest>,
+) -> Result<StatusCode> {
+    blocks::block_user(&state.db, &auth.user_id, &payload.user_id).await?;
+    tracing::info!(user_id = %auth.user_id, blocked = %payload.user_id, "User blocked");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn unblock_user(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(user_id): Path<String>,
+) -> Result<StatusCode> {
+    if !blocks::unblock_user(&state.db, &auth.user_id, &user_id).await? {
+        return Err(AppError::not_found("Blocked user"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_trusted_traders(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<Vec<TrustedTrader>>> {
+    let traders = trading::list_trusted_traders(&state.db, &auth.user_id).await?;
+
+    Ok(Json(traders))
+}
+
+async fn trust_trader(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<TrustTraderRequest>,
+) -> Result<StatusCode> {
+    trading::trust_trader(&state.db, &auth.user_id, &payload.user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn untrust_trader(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(user_id): Path<String>,
+) -> Result<StatusCode> {
+    if !trading::untrust_trader(&state.db, &auth.user_id, &user_id).await? {
+        return Err(AppError::not_found("Trusted trader"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_trade_settings(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<TradeSettings>> {
+    Ok(Json(
+        trading::trade_settings(&state.db, &auth.user_id).await?,
+    ))
+}
+
+/// Choose who may propose trades, and whether gifts from trusted traders are accepted automatically
+async fn update_trade_settings(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<TradeSettings>,
+) -> Result<Json<TradeSettings>> {
+    trading::update_trade_settings(&state.db, &auth.user_id, &payload).await?;
+
+    Ok(Json(payload))
+}
@@ -1,12 +1,38 @@
-use axum::{routing::get, Router};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    middleware as axum_middleware,
+    routing::{get, post},
+    Json, Router,
+};
 use std::sync::Arc;
 
-use crate::AppState;
+use crate::{
+    api::{
+        extractors::FreshTotp,
+        middleware::{
+            auth::{AuthClient, AuthUser},
+            permissions::RequirePermission,
+            signature::require_signed_client,
+        },
+    },
+    models::{CreateTradeRequest, Trade},
+    services::{game_clients, trading},
+    utils::{errors::Result, AppError},
+    AppState,
+};
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/", get(get_trades))
+        .route("/", get(get_trades).post(propose_trade))
         .route("/active", get(get_active_trades))
+        .route("/:id/accept", post(accept_trade))
+        .route(
+            "/:id/settle",
+            post(settle_trade)
+                .route_layer(RequirePermission("trades:settle"))
+                .route_layer(axum_middleware::from_fn(require_signed_client)),
+        )
 }
 
 async fn get_trades() -> &'static str {
@@ -16,3 +42,64 @@ async fn get_trades() -> &'static str {
 async fn get_active_trades() -> &'static str {
     "Get active trades endpoint"
 }
+
+/// Offer items to another player
+async fn propose_trade(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<CreateTradeRequest>,
+) -> Result<(StatusCode, Json<Trade>)> {
+    let trade = trading::propose_trade(&state.db, &auth.user_id, &payload).await?;
+    tracing::info!(
+        trade_id = %trade.id,
+        initiator = %auth.user_id,
+        status = %trade.status,
+        "Trade proposed"
+    );
+
+    Ok((StatusCode::CREATED, Json(trade)))
+}
+
+/// Accept a trade offered to the caller; the items change hands immediately
+async fn accept_trade(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    _fresh_totp: FreshTotp,
+    Path(trade_id): Path<String>,
+) -> Result<Json<Trade>> {
+    let trade = trading::accept_trade(&state.db, &auth.user_id, &trade_id).await?;
+    tracing::info!(trade_id = %trade.id, recipient = %auth.user_id, "Trade completed");
+
+    Ok(Json(trade))
+}
+
+/// Settle a pending trade that two players agreed to in game. Game servers only,
+/// and only for their own players and items.
+async fn settle_trade(
+    State(state): State<Arc<AppState>>,
+    client: AuthClient,
+    Path(trade_id): Path<String>,
+) -> Result<Json<Trade>> {
+    let trade = trading::find_trade(&state.db, &trade_id).await?;
+
+    for user_id in [&trade.initiator_id, &trade.recipient_id] {
+        if !game_clients::can_act_for(&state.db, &client.allowed_games, user_id).await? {
+            return Err(AppError::forbidden(
+                "This API key can't act for players outside its allowed games",
+            ));
+        }
+    }
+
+    for game in trading::trade_item_games(&state.db, &trade.id).await? {
+        if !game_clients::allows_item_game(&client.allowed_games, game.as_deref()) {
+            return Err(AppError::forbidden(
+                "This API key can't settle trades of items from outside its allowed games",
+            ));
+        }
+    }
+
+    let trade = trading::settle_pending_trade(&state.db, &trade.id).await?;
+    tracing::info!(trade_id = %trade.id, client_id = %client.client_id, "Trade settled by game server");
+
+    Ok(Json(trade))
+}
@@ -1,18 +1,454 @@
-use axum::{routing::get, Router};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
 use std::sync::Arc;
 
-use crate::AppState;
+use crate::{
+    api::{
+        auth::{check_login_throttle, record_login_attempt},
+        extractors::ClientInfo,
+        middleware::auth::AuthUser,
+    },
+    models::{
+        AccountDeletionResponse, ChangePasswordRequest, DeleteAccountRequest, LinkGameRequest,
+        PrivacySettings, PublicProfile, RecoveryCodesResponse, RevokeSessionsResponse, SessionInfo,
+        TotpCodeRequest, TotpSetupResponse, TwoFactorStatus, UpdateUserRequest, User, UserGame,
+        UserSearchQuery, UserSearchResponse,
+    },
+    services::{accounts, games, login_attempts::LoginOutcome, mfa, profiles, sessions},
+    utils::{errors::Result, AppError},
+    AppState,
+};
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/me", get(get_current_user))
+        .route(
+            "/me",
+            get(get_current_user)
+                .patch(update_current_user)
+                .delete(delete_account),
+        )
+        .route("/me/password", put(change_password))
+        .route(
+            "/me/privacy",
+            get(get_privacy_settings).put(update_privacy_settings),
+        )
+        .route("/me/games", get(get_linked_games).post(link_game))
+        .route("/me/games/:game_name", delete(unlink_game))
+        .route("/me/export", get(export_account))
+        .route(
+            "/me/sessions",
+            get(get_sessions).delete(revoke_other_sessions),
+        )
+        .route("/me/sessions/:id", delete(revoke_session))
+        .route("/me/2fa", get(get_two_factor_status))
+        .route("/me/2fa/setup", post(setup_two_factor))
+        .route("/me/2fa/enable", post(enable_two_factor))
+        .route("/me/2fa/disable", post(disable_two_factor))
+        .route("/me/2fa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/search", get(search_users))
         .route("/:id", get(get_user))
 }
 
-async fn get_current_user() -> &'static str {
-    "Get current user endpoint"
+async fn get_current_user(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<User>> {
+    Ok(Json(find_current_user(&state, &auth).await?))
 }
 
-async fn get_user() -> &'static str {
-    "Get user by ID endpoint"
+/// Change the caller's username and/or avatar; omitted fields are left as they are
+async fn update_current_user(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<UpdateUserRequest>,
+) -> Result<Json<User>> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let mut user = find_current_user(&state, &auth).await?;
+    if let Some(username) = payload.username {
+        user.username = username;
+    }
+    if let Some(avatar_url) = payload.avatar_url {
+        user.avatar_url = Some(avatar_url);
+    }
+
+    sqlx::query("UPDATE users SET username = ?, avatar_url = ? WHERE id = ?")
+        .bind(&user.username)
+        .bind(&user.avatar_url)
+        .bind(&user.id)
+        .execute(&state.db)
+        .await
+        .map_err(|e| {
+            if e.to_string().contains("Duplicate entry") {
+                AppError::Conflict("Username is already taken".to_string())
+            } else {
+                AppError::Database(e)
+            }
+        })?;
+
+    // Pick up the new updated_at
+    Ok(Json(find_current_user(&state, &auth).await?))
+}
+
+/// Set a new password. Needs the current one; other devices are signed out.
+async fn change_password(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<StatusCode> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let user = find_current_user(&state, &auth).await?;
+    if !verify_password(&state, &user, &client, &payload.current_password).await? {
+        return Err(AppError::forbidden("Incorrect password"));
+    }
+
+    let password_hash = state.passwords.hash(&payload.new_password)?;
+    sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+        .bind(&password_hash)
+        .bind(&user.id)
+        .execute(&state.db)
+        .await?;
+
+    let revoked =
+        sessions::revoke_other_sessions(&state.db, &auth.user_id, &auth.session_id).await?;
+    tracing::info!(user_id = %auth.user_id, revoked, "Password changed, other sessions revoked");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Another player's public profile, limited by their privacy settings
+async fn get_user(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(user_id): Path<String>,
+) -> Result<Json<PublicProfile>> {
+    let profile = profiles::public_profile(&state.db, &auth.user_id, &user_id).await?;
+
+    Ok(Json(profile))
+}
+
+/// Find other players by username or linked game account
+async fn search_users(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Query(query): Query<UserSearchQuery>,
+) -> Result<Json<UserSearchResponse>> {
+    let results = profiles::search_users(&state.db, &auth.user_id, &query).await?;
+
+    Ok(Json(results))
+}
+
+async fn get_privacy_settings(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<PrivacySettings>> {
+    Ok(Json(
+        profiles::privacy_settings(&state.db, &auth.user_id).await?,
+    ))
+}
+
+async fn update_privacy_settings(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<PrivacySettings>,
+) -> Result<Json<PrivacySettings>> {
+    profiles::update_privacy_settings(&state.db, &auth.user_id, &payload).await?;
+
+    Ok(Json(payload))
+}
+
+async fn get_linked_games(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<Vec<UserGame>>> {
+    Ok(Json(
+        games::list_user_games(&state.db, &auth.user_id).await?,
+    ))
+}
+
+/// Link a game account with a code the game server showed the player in game
+async fn link_game(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<LinkGameRequest>,
+) -> Result<(StatusCode, Json<UserGame>)> {
+    use validator::Validate;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let game = games::link_game(
+        &state.db,
+        &state.config.game_link_throttle,
+        &auth.user_id,
+        client.ip_address.as_deref(),
+        &payload.code,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(game)))
+}
+
+async fn unlink_game(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(game_name): Path<String>,
+) -> Result<StatusCode> {
+    if !games::unlink_game(&state.db, &auth.user_id, &game_name).await? {
+        return Err(AppError::not_found("Linked game"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_sessions(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<Vec<SessionInfo>>> {
+    let sessions = sessions::list_active_sessions(&state.db, &auth.user_id).await?;
+
+    Ok(Json(
+        sessions
+            .iter()
+            .map(|session| session.to_info(&auth.session_id))
+            .collect(),
+    ))
+}
+
+async fn revoke_session(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode> {
+    if !sessions::revoke_user_session(&state.db, &auth.user_id, &session_id).await? {
+        return Err(AppError::not_found("Session"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Sign out everywhere except the device making the request
+async fn revoke_other_sessions(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<RevokeSessionsResponse>> {
+    let revoked =
+        sessions::revoke_other_sessions(&state.db, &auth.user_id, &auth.session_id).await?;
+
+    Ok(Json(RevokeSessionsResponse { revoked }))
+}
+
+async fn get_two_factor_status(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<TwoFactorStatus>> {
+    let enabled_at = mfa::enabled_at(&state.db, &auth.user_id).await?;
+    let recovery_codes_remaining = mfa::recovery_codes_remaining(&state.db, &auth.user_id).await?;
+
+    Ok(Json(TwoFactorStatus {
+        enabled: enabled_at.is_some(),
+        enabled_at,
+        recovery_codes_remaining,
+    }))
+}
+
+/// Start TOTP enrollment. Nothing is enforced until the first code is confirmed.
+async fn setup_two_factor(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<Json<TotpSetupResponse>> {
+    let (email,): (String,) = sqlx::query_as("SELECT email FROM users WHERE id = ?")
+        .bind(&auth.user_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    let enrollment = mfa::begin_enrollment(&state.db, &auth.user_id, &email).await?;
+
+    Ok(Json(TotpSetupResponse {
+        secret: enrollment.secret,
+        provisioning_uri: enrollment.provisioning_uri,
+    }))
+}
+
+async fn enable_two_factor(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>> {
+    let recovery_codes = mfa::confirm_enrollment(&state.db, &auth.user_id, &payload.code).await?;
+    tracing::info!(user_id = %auth.user_id, "Two-factor authentication enabled");
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Turn off two-factor authentication; needs a current TOTP or recovery code
+async fn disable_two_factor(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Result<StatusCode> {
+    if !mfa::is_enabled(&state.db, &auth.user_id).await? {
+        return Err(AppError::bad_request(
+            "Two-factor authentication is not enabled",
+        ));
+    }
+
+    let user = find_current_user(&state, &auth).await?;
+    if !verify_mfa_code(&state, &user, &client, &payload.code, true).await? {
+        return Err(AppError::forbidden("Invalid two-factor code"));
+    }
+
+    mfa::disable(&state.db, &auth.user_id).await?;
+    tracing::info!(user_id = %auth.user_id, "Two-factor authentication disabled");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Replace the recovery codes, invalidating the old set; needs a current TOTP code
+async fn regenerate_recovery_codes(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>> {
+    let user = find_current_user(&state, &auth).await?;
+    if !verify_mfa_code(&state, &user, &client, &payload.code, false).await? {
+        return Err(AppError::forbidden("Invalid two-factor code"));
+    }
+
+    let recovery_codes = mfa::regenerate_recovery_codes(&state.db, &auth.user_id).await?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Download everything stored about the caller as a JSON file
+async fn export_account(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse> {
+    let export = accounts::export(&state.db, &auth.user_id).await?;
+
+    tracing::info!(user_id = %auth.user_id, "Account data exported");
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"bridge-export-{}.json\"",
+                auth.user_id
+            ),
+        )],
+        Json(export),
+    ))
+}
+
+/// Delete the caller's account. Needs the password, and a TOTP or recovery code
+/// when two-factor authentication is enabled.
+async fn delete_account(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> Result<Json<AccountDeletionResponse>> {
+    let user = find_current_user(&state, &auth).await?;
+
+    if !verify_password(&state, &user, &client, &payload.password).await? {
+        return Err(AppError::forbidden("Incorrect password"));
+    }
+
+    if mfa::is_enabled(&state.db, &auth.user_id).await? {
+        let code = payload
+            .code
+            .as_deref()
+            .ok_or_else(|| AppError::forbidden("A two-factor code is required"))?;
+        if !verify_mfa_code(&state, &user, &client, code, true).await? {
+            return Err(AppError::forbidden("Invalid two-factor code"));
+        }
+    }
+
+    let deleted_at = accounts::delete_account(&state.db, &state.passwords, &auth.user_id).await?;
+
+    Ok(Json(AccountDeletionResponse {
+        deleted_at,
+        purge_after: deleted_at + state.config.account_deletion_grace,
+    }))
+}
+
+/// Check the caller's password under the login backoff, so a stolen session
+/// can't be used to guess it
+async fn verify_password(
+    state: &AppState,
+    user: &User,
+    client: &ClientInfo,
+    password: &str,
+) -> Result<bool> {
+    check_login_throttle(state, &user.email, client).await?;
+
+    let valid = state.passwords.verify(password, &user.password_hash)?;
+    if !valid {
+        record_login_attempt(
+            state,
+            &user.email,
+            Some(&user.id),
+            client,
+            LoginOutcome::InvalidPassword,
+        )
+        .await?;
+    }
+
+    Ok(valid)
+}
+
+/// Check a TOTP code, or a recovery code when allowed, under the login backoff so
+/// short codes can't be guessed through account settings either
+async fn verify_mfa_code(
+    state: &AppState,
+    user: &User,
+    client: &ClientInfo,
+    code: &str,
+    allow_recovery_code: bool,
+) -> Result<bool> {
+    check_login_throttle(state, &user.email, client).await?;
+
+    let valid = if allow_recovery_code {
+        mfa::verify(&state.db, &user.id, code).await?
+    } else {
+        mfa::verify_totp(&state.db, &user.id, code).await?
+    };
+
+    if !valid {
+        record_login_attempt(
+            state,
+            &user.email,
+            Some(&user.id),
+            client,
+            LoginOutcome::InvalidMfaCode,
+        )
+        .await?;
+    }
+
+    Ok(valid)
+}
+
+async fn find_current_user(state: &AppState, auth: &AuthUser) -> Result<User> {
+    sqlx::query_as("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL")
+        .bind(&auth.user_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::not_found("User"))
 }
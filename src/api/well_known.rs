@@ -0,0 +1,17 @@
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Json, Router};
+use std::sync::Arc;
+
+use crate::AppState;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/jwks.json", get(jwks))
+}
+
+/// Public keys for verifying Bridge-issued tokens without calling the API.
+/// Empty while tokens are signed with HS256.
+async fn jwks(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(state.signing_keys.jwks().await),
+    )
+}
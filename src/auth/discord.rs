@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use oauth2::{
+    basic::BasicClient, reqwest::async_http_client, AuthType, AuthUrl, AuthorizationCode, ClientId,
+    ClientSecret, CsrfToken, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope,
+    TokenResponse, TokenUrl,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{
+    auth::{AuthorizationRequest, ExternalIdentity, IdentityProvider},
+    config::DiscordConfig,
+    utils::errors::{AppError, Result},
+};
+
+/// Discord OAuth2 client using the authorization code flow with PKCE
+pub struct DiscordProvider {
+    client: BasicClient,
+    http: reqwest::Client,
+    api_url: String,
+}
+
+/// The subset of Discord's `/users/@me` response we use
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscordUser {
+    pub id: String,
+    pub username: String,
+    pub global_name: Option<String>,
+    pub avatar: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub verified: bool,
+}
+
+impl DiscordProvider {
+    pub fn new(config: &DiscordConfig) -> Result<Self> {
+        let invalid_url = |e: oauth2::url::ParseError| {
+            AppError::Internal(format!("Invalid Discord OAuth configuration: {}", e))
+        };
+
+        let client = BasicClient::new(
+            ClientId::new(config.client_id.clone()),
+            Some(ClientSecret::new(config.client_secret.clone())),
+            AuthUrl::new(config.auth_url.clone()).map_err(invalid_url)?,
+            Some(TokenUrl::new(config.token_url.clone()).map_err(invalid_url)?),
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_redirect_uri(RedirectUrl::new(config.redirect_url.clone()).map_err(invalid_url)?);
+
+        Ok(Self {
+            client,
+            http: reqwest::Client::new(),
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Exchange an authorization code and fetch the Discord account it grants access to
+    async fn fetch_user(&self, code: &str, pkce_verifier: &str) -> Result<DiscordUser> {
+        let token = self
+            .client
+            .exchange_code(AuthorizationCode::new(code.to_string()))
+            .set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier.to_string()))
+            .request_async(async_http_client)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Discord code exchange failed: {}", e);
+                AppError::Authentication("Discord authorization failed".to_string())
+            })?;
+
+        let user = self
+            .http
+            .get(format!("{}/users/@me", self.api_url))
+            .bearer_auth(token.access_token().secret())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::Internal(format!("Failed to fetch Discord user: {}", e)))?
+            .json::<DiscordUser>()
+            .await
+            .map_err(|e| AppError::Internal(format!("Invalid Discord user response: {}", e)))?;
+
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for DiscordProvider {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    fn authorize(&self, state: &str) -> Result<AuthorizationRequest> {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+        let (url, _) = self
+            .client
+            .authorize_url(|| CsrfToken::new(state.to_string()))
+            .add_scope(Scope::new("identify".to_string()))
+            .add_scope(Scope::new("email".to_string()))
+            .set_pkce_challenge(pkce_challenge)
+            .url();
+
+        Ok(AuthorizationRequest {
+            url: url.to_string(),
+            pkce_verifier: Some(pkce_verifier.secret().clone()),
+        })
+    }
+
+    async fn complete(
+        &self,
+        params: &HashMap<String, String>,
+        pkce_verifier: Option<&str>,
+    ) -> Result<ExternalIdentity> {
+        if let Some(error) = params.get("error") {
+            return Err(AppError::Authentication(format!(
+                "Discord authorization denied: {}",
+                error
+            )));
+        }
+
+        let code = params
+            .get("code")
+            .ok_or_else(|| AppError::bad_request("Missing authorization code"))?;
+        let pkce_verifier = pkce_verifier
+            .ok_or_else(|| AppError::Authentication("Missing PKCE verifier".to_string()))?;
+
+        let user = self.fetch_user(code, pkce_verifier).await?;
+
+        let avatar_url = user.avatar.as_ref().map(|avatar| {
+            format!(
+                "https://cdn.discordapp.com/avatars/{}/{}.png",
+                user.id, avatar
+            )
+        });
+
+        Ok(ExternalIdentity {
+            provider_user_id: user.id,
+            username: Some(user.username),
+            email: user.email,
+            email_verified: user.verified,
+            avatar_url,
+        })
+    }
+}
@@ -1 +1,7 @@
-// Auth module - OAuth providers will be implemented here
+// Auth module - identity providers live here
+pub mod discord;
+pub mod provider;
+pub mod registry;
+
+pub use provider::*;
+pub use registry::*;
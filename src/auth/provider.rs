@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use crate::utils::errors::Result;
+
+/// Where to send the user to start authenticating with a provider
+pub struct AuthorizationRequest {
+    pub url: String,
+    /// PKCE verifier to keep until the callback, for providers that use PKCE
+    pub pkce_verifier: Option<String>,
+}
+
+/// An account at an external identity provider, as reported by that provider
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    /// Stable identifier of the account at the provider
+    pub provider_user_id: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub avatar_url: Option<String>,
+}
+
+/// A source of player identities (Discord, Steam, game launchers, ...).
+///
+/// Providers only speak their own protocol; state storage, account lookup,
+/// registration and linking are handled generically by the auth routes.
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Name used in routes and stored in `user_identities.provider`
+    fn name(&self) -> &'static str;
+
+    /// Build the URL that starts the flow, embedding the opaque `state` value
+    /// so it comes back on the callback
+    fn authorize(&self, state: &str) -> Result<AuthorizationRequest>;
+
+    /// Verify the callback query parameters and resolve the external identity
+    async fn complete(
+        &self,
+        params: &HashMap<String, String>,
+        pkce_verifier: Option<&str>,
+    ) -> Result<ExternalIdentity>;
+}
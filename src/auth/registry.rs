@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::auth::IdentityProvider;
+
+/// Identity providers enabled for this deployment, keyed by name
+#[derive(Default, Clone)]
+pub struct ProviderRegistry {
+    providers: HashMap<&'static str, Arc<dyn IdentityProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, provider: impl IdentityProvider + 'static) {
+        let name = provider.name();
+        if self.providers.insert(name, Arc::new(provider)).is_some() {
+            tracing::warn!("Identity provider {} registered twice", name);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn IdentityProvider>> {
+        self.providers.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.providers.keys().copied().collect();
+        names.sort();
+        names
+    }
+}
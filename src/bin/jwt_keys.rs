@@ -0,0 +1,99 @@
+use bridge::{config::Config, utils::signing_keys};
+use sqlx::mysql::MySqlPoolOptions;
+use std::env;
+
+const USAGE: &str = "Usage: jwt_keys [list | rotate | prune]
+
+Commands:
+  list      Show signing keys and their state (default)
+  rotate    Create a new signing key (using JWT_ALGORITHM) and retire the current one
+  prune     Delete retired keys whose grace period has ended
+
+Running servers start signing with a rotated key within a minute; tokens signed
+by the previous key stay valid for JWT_KEY_GRACE_MINUTES.";
+
+enum Command {
+    List,
+    Rotate,
+    Prune,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] | ["list"] => Command::List,
+        ["rotate"] => Command::Rotate,
+        ["prune"] => Command::Prune,
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+    let config = Config::from_env();
+    let grace = config.jwt_key_grace;
+
+    let pool = MySqlPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await?;
+
+    match command {
+        Command::List => {
+            let keys = signing_keys::list_signing_keys(&pool).await?;
+            if keys.is_empty() {
+                println!("No signing keys yet; one is created when the server starts");
+            }
+
+            for key in &keys {
+                let state = match key.retired_at {
+                    None => "active".to_string(),
+                    Some(_) if key.accepts_tokens(grace) => "grace".to_string(),
+                    Some(_) => "expired".to_string(),
+                };
+                let retired_at = key
+                    .retired_at
+                    .map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+                println!(
+                    "  {:<24} {:<6} {:<8} created {}  {}",
+                    key.kid,
+                    format!("{:?}", key.algorithm),
+                    state,
+                    key.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    if retired_at.is_empty() {
+                        String::new()
+                    } else {
+                        format!("retired {}", retired_at)
+                    }
+                );
+            }
+        }
+        Command::Rotate => {
+            let key = signing_keys::rotate_signing_key(&pool, config.jwt_algorithm).await?;
+            println!(
+                "✓ New {:?} signing key {} is now active",
+                key.algorithm, key.kid
+            );
+            println!(
+                "  Previous keys keep validating tokens for {} minutes",
+                grace.num_minutes()
+            );
+        }
+        Command::Prune => {
+            let deleted = signing_keys::prune_signing_keys(&pool, grace).await?;
+            println!("✓ Deleted {} expired signing keys", deleted);
+        }
+    }
+
+    Ok(())
+}
@@ -1,12 +1,56 @@
+use bridge::db::migrations::{self, Migration, MigrationState};
 use sqlx::mysql::MySqlPoolOptions;
 use std::env;
-use std::fs;
 use std::path::Path;
 
+const USAGE: &str = "Usage: migrate [status | up | down <n>] [--dry-run]
+
+Commands:
+  status      Show applied and pending migrations
+  up          Apply all pending migrations (default)
+  down <n>    Revert the last <n> applied migrations
+
+Options:
+  --dry-run   Print what would be executed without touching the database";
+
+enum Command {
+    Status,
+    Up,
+    Down(usize),
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
 
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    args.retain(|arg| arg != "--dry-run");
+
+    let command = match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] | ["up"] => Command::Up,
+        ["status"] => Command::Status,
+        ["down", count] => match count.parse::<usize>() {
+            Ok(count) if count > 0 => Command::Down(count),
+            _ => {
+                eprintln!(
+                    "down expects a positive number of migrations to revert\n\n{}",
+                    USAGE
+                );
+                std::process::exit(2);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
 
     println!("Connecting to database...");
@@ -15,153 +59,151 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .connect(&database_url)
         .await?;
 
-    println!("Running migrations...");
-
-    // Get a connection from the pool
     let mut conn = pool.acquire().await?;
 
-    // Disable foreign key checks
-    sqlx::query("SET FOREIGN_KEY_CHECKS = 0")
-        .execute(&mut *conn)
-        .await?;
+    let available = migrations::load_migrations(Path::new("migrations"))?;
+    let applied = migrations::applied_migrations(&mut conn).await?;
 
-    let migrations_dir = Path::new("migrations");
-    let mut entries: Vec<_> = fs::read_dir(migrations_dir)?
-        .filter_map(Result::ok)
-        .filter(|entry| {
-            entry
-                .path()
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext == "sql")
-                .unwrap_or(false)
-        })
-        .collect();
-
-    entries.sort_by_key(|entry| entry.path());
-
-    for entry in entries {
-        let path = entry.path();
-        let filename = path
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or("unknown");
-
-        println!("Running migration: {}", filename);
-
-        let sql = fs::read_to_string(&path)?;
-
-        // Parse SQL statements properly
-        let mut statements = Vec::new();
-        let mut current_statement = String::new();
-        let mut in_string = false;
-        let mut string_delimiter = ' ';
-        
-        for line in sql.lines() {
-            let trimmed = line.trim();
-            
-            // Skip comment-only lines
-            if trimmed.starts_with("--") || trimmed.is_empty() {
-                continue;
+    match command {
+        Command::Status => {
+            let statuses = migrations::status(&available, &applied);
+            if statuses.is_empty() {
+                println!("No migrations found");
             }
-            
-            // Track string literals to avoid splitting on semicolons inside strings
-            for ch in line.chars() {
-                if !in_string && (ch == '\'' || ch == '"') {
-                    in_string = true;
-                    string_delimiter = ch;
-                } else if in_string && ch == string_delimiter {
-                    in_string = false;
-                }
-                
-                current_statement.push(ch);
-                
-                if ch == ';' && !in_string {
-                    let stmt = current_statement.trim().to_string();
-                    if !stmt.is_empty() {
-                        statements.push(stmt);
-                    }
-                    current_statement.clear();
-                }
+
+            for status in &statuses {
+                let applied_at = status
+                    .applied_at
+                    .map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+                println!(
+                    "  {:03}  {:<40} {:<9} {}",
+                    status.version, status.name, status.state, applied_at
+                );
+            }
+
+            let pending = statuses
+                .iter()
+                .filter(|s| s.state == MigrationState::Pending)
+                .count();
+            println!("\n{} applied, {} pending", applied.len(), pending);
+
+            if statuses
+                .iter()
+                .any(|s| matches!(s.state, MigrationState::Modified | MigrationState::Missing))
+            {
+                eprintln!("⚠️  Applied migrations differ from the files on disk");
+                std::process::exit(1);
             }
-            current_statement.push('\n');
         }
-        
-        // Add any remaining statement
-        let remaining = current_statement.trim();
-        if !remaining.is_empty() && !remaining.starts_with("--") {
-            statements.push(remaining.to_string());
+        Command::Up => {
+            migrations::verify_applied(&available, &applied)?;
+
+            let pending = migrations::pending(&available, &applied);
+            if pending.is_empty() {
+                println!("Database is up to date");
+                return Ok(());
+            }
+
+            if dry_run {
+                println!(
+                    "Dry run: {} pending migrations would be applied",
+                    pending.len()
+                );
+                for migration in pending {
+                    print_plan(migration, &migration.up_sql);
+                }
+                return Ok(());
+            }
+
+            migrations::ensure_migrations_table(&mut conn).await?;
+
+            for migration in pending {
+                println!("Applying migration: {}", migration.filename());
+                let executed = migrations::apply(&mut conn, migration).await?;
+                println!("  ✓ {} statements executed", executed);
+            }
+
+            println!("All migrations completed successfully!");
         }
+        Command::Down(count) => {
+            migrations::verify_applied(&available, &applied)?;
+
+            let targets = migrations::to_revert(&available, &applied, count)?;
+            if targets.is_empty() {
+                println!("No applied migrations to revert");
+                return Ok(());
+            }
+
+            // Check every down script up front so we never stop halfway
+            for migration in &targets {
+                if migration.down_sql.is_none() {
+                    return Err(migrations::MigrationError::MissingDownScript(
+                        migration.filename(),
+                    )
+                    .into());
+                }
+            }
 
-        let total_statements = statements.len();
-        println!("  Found {} statements to execute", total_statements);
-
-        let mut table_count = 0;
-        let mut success_count = 0;
-        
-        for (i, statement) in statements.iter().enumerate() {
-            // Create a preview of the statement
-            let first_line = statement.lines().next().unwrap_or("");
-            let preview = if first_line.len() > 80 {
-                format!("{}...", &first_line[..80])
-            } else {
-                first_line.to_string()
-            };
-            
-            println!("  [{}/{}] Executing: {}", i + 1, total_statements, preview);
-
-            match sqlx::query(statement).execute(&mut *conn).await {
-                Ok(result) => {
-                    success_count += 1;
-                    if statement.to_uppercase().contains("CREATE TABLE") {
-                        table_count += 1;
-                        println!("    ✓ Created table");
-                    } else if statement.to_uppercase().starts_with("INSERT") {
-                        println!("    ✓ Inserted {} rows", result.rows_affected());
-                    } else if statement.to_uppercase().starts_with("DROP") {
-                        println!("    ✓ Dropped table");
-                    } else {
-                        println!("    ✓ Success");
-                    }
-                },
-                Err(e) => {
-                    eprintln!("    ✗ Error: {}", e);
-                    eprintln!("    Statement: {}", statement);
-                    
-                    // Re-enable foreign key checks before exiting
-                    sqlx::query("SET FOREIGN_KEY_CHECKS = 1")
-                        .execute(&mut *conn)
-                        .await
-                        .ok();
-                    
-                    return Err(format!("Migration {} failed at statement {}: {}", filename, i + 1, e).into());
+            if dry_run {
+                println!("Dry run: {} migrations would be reverted", targets.len());
+                for migration in targets {
+                    print_plan(migration, migration.down_sql.as_deref().unwrap_or_default());
                 }
+                return Ok(());
             }
+
+            for migration in targets {
+                println!("Reverting migration: {}", migration.filename());
+                let executed = migrations::revert(&mut conn, migration).await?;
+                println!("  ✓ {} statements executed", executed);
+            }
+
+            println!("Rollback completed successfully!");
         }
+    }
 
-        println!("✓ Migration {} completed: {}/{} statements successful, {} tables created", 
-                 filename, success_count, total_statements, table_count);
+    Ok(())
+}
+
+fn print_plan(migration: &Migration, sql: &str) {
+    let statements = migrations::split_statements(sql);
+    println!(
+        "\n{} ({} statements)",
+        migration.filename(),
+        statements.len()
+    );
+
+    for (i, statement) in statements.iter().enumerate() {
+        println!("  [{}/{}] {}", i + 1, statements.len(), preview(statement));
     }
+}
+
+/// First line of a statement, cut to 80 characters
+fn preview(statement: &str) -> String {
+    let first_line = statement.lines().next().unwrap_or("");
+    if first_line.chars().count() > 80 {
+        format!("{}...", first_line.chars().take(80).collect::<String>())
+    } else {
+        first_line.to_string()
+    }
+}
 
-    // Re-enable foreign key checks
-    sqlx::query("SET FOREIGN_KEY_CHECKS = 1")
-        .execute(&mut *conn)
-        .await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Verify tables were created
-    let count: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = DATABASE()"
-    )
-    .fetch_one(&pool)
-    .await?;
-    
-    println!("\nTotal tables in database: {}", count.0);
-    
-    if count.0 == 0 {
-        eprintln!("⚠️  WARNING: No tables were created!");
+    #[test]
+    fn preview_keeps_short_first_lines() {
+        assert_eq!(preview("SELECT 1;\nSELECT 2;"), "SELECT 1;");
     }
 
-    println!("All migrations completed successfully!");
+    #[test]
+    fn preview_cuts_on_character_boundaries() {
+        let statement = format!("INSERT INTO t VALUES ('{}');", "é".repeat(100));
+        let cut = preview(&statement);
 
-    Ok(())
-}
\ No newline at end of file
+        assert!(cut.ends_with("..."));
+        assert_eq!(cut.chars().count(), 83);
+    }
+}
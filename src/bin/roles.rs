@@ -0,0 +1,96 @@
+use bridge::services::roles;
+use sqlx::mysql::MySqlPoolOptions;
+use std::env;
+
+const USAGE: &str =
+    "Usage: roles [list | show <email> | grant <email> <role> | revoke <email> <role>]
+
+Commands:
+  list      Show roles and their permissions (default)
+  show      Show the roles held by a user
+  grant     Give a user a role, e.g. to bootstrap the first admin
+  revoke    Take a role away from a user
+
+Granted roles are picked up the next time the user's access token is issued;
+revoked roles stop granting permissions immediately.";
+
+enum Command {
+    List,
+    Show(String),
+    Grant(String, String),
+    Revoke(String, String),
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] | ["list"] => Command::List,
+        ["show", email] => Command::Show(email.to_string()),
+        ["grant", email, role] => Command::Grant(email.to_string(), role.to_string()),
+        ["revoke", email, role] => Command::Revoke(email.to_string(), role.to_string()),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+
+    let pool = MySqlPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await?;
+
+    match command {
+        Command::List => {
+            for role in roles::list_roles(&pool).await? {
+                println!("  {:<12} {}", role.name, role.permissions.join(", "));
+            }
+        }
+        Command::Show(email) => {
+            let user_id = find_user(&pool, &email).await?;
+            let held = roles::user_roles(&pool, &user_id).await?;
+            if held.is_empty() {
+                println!("{} has no roles", email);
+            } else {
+                println!("{}: {}", email, held.join(", "));
+            }
+        }
+        Command::Grant(email, role) => {
+            let user_id = find_user(&pool, &email).await?;
+            roles::assign_role(&pool, &user_id, &role, None).await?;
+            println!("✓ Granted '{}' to {}", role, email);
+        }
+        Command::Revoke(email, role) => {
+            let user_id = find_user(&pool, &email).await?;
+            if roles::remove_role(&pool, &user_id, &role).await? {
+                println!("✓ Revoked '{}' from {}", role, email);
+            } else {
+                println!("{} did not have '{}'", email, role);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn find_user(
+    pool: &sqlx::MySqlPool,
+    email: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let user: Option<(String,)> = sqlx::query_as("SELECT id FROM users WHERE email = ?")
+        .bind(email)
+        .fetch_optional(pool)
+        .await?;
+
+    user.map(|(id,)| id)
+        .ok_or_else(|| format!("No user with email {}", email).into())
+}
@@ -0,0 +1,211 @@
+use chrono::Duration;
+use jsonwebtoken::Algorithm;
+use std::env;
+
+/// Runtime configuration loaded from environment variables
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Lifetime of access JWTs
+    pub access_token_ttl: Duration,
+    /// Lifetime of refresh tokens, extended on every rotation
+    pub refresh_token_ttl: Duration,
+    /// How long a retired JWT signing key keeps validating tokens
+    pub jwt_key_grace: Duration,
+    /// Algorithm for new signing keys: HS256, EdDSA or RS256
+    pub jwt_algorithm: Algorithm,
+    /// Read client IPs from `X-Forwarded-For` (only enable behind a trusted proxy)
+    pub trust_proxy_headers: bool,
+    /// Discord OAuth2 settings, `None` when `DISCORD_CLIENT_ID` is unset
+    pub discord: Option<DiscordConfig>,
+    /// Public URL of the web client, used to build links in emails
+    pub app_url: String,
+    /// Lifetime of password reset links
+    pub password_reset_ttl: Duration,
+    /// Lifetime of email verification links
+    pub email_verification_ttl: Duration,
+    /// Block trading and marketplace actions until the user's email is verified
+    pub require_verified_email: bool,
+    /// Lifetime of the challenge token between the password and two-factor login steps
+    pub mfa_challenge_ttl: Duration,
+    /// Ask users with 2FA enabled for a fresh code on high-value actions
+    pub require_totp_for_high_value: bool,
+    /// How long a deleted account's remaining data is kept before it is purged
+    pub account_deletion_grace: Duration,
+    /// How far a signed request's timestamp may be from the server clock
+    pub signature_max_skew: Duration,
+    /// Lifetime of the codes game servers issue for linking a game account
+    pub game_link_code_ttl: Duration,
+    pub password_policy: PasswordPolicyConfig,
+    pub login_throttle: LoginThrottleConfig,
+    /// Backoff for wrong game link codes; the account limit applies per user
+    pub game_link_throttle: LoginThrottleConfig,
+    pub mail: MailConfig,
+}
+
+/// Argon2id cost for new password hashes. Stored hashes made with other settings
+/// are rehashed the next time their owner signs in.
+#[derive(Debug, Clone)]
+pub struct PasswordPolicyConfig {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Mix a server-side secret from `app_secrets` into new hashes
+    pub pepper: bool,
+}
+
+/// Failed-login backoff. Once an account or IP reaches its failure limit within
+/// `window`, further attempts are refused for `base_delay`, doubling with each
+/// additional failure up to `max_delay`.
+#[derive(Debug, Clone)]
+pub struct LoginThrottleConfig {
+    pub max_account_failures: i64,
+    pub max_ip_failures: i64,
+    pub window: Duration,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscordConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_url: String,
+    pub auth_url: String,
+    /// Overridable so tests can point the code exchange at a local mock server
+    pub token_url: String,
+    pub api_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailConfig {
+    pub from: String,
+    pub transport: MailTransport,
+}
+
+#[derive(Debug, Clone)]
+pub enum MailTransport {
+    Smtp {
+        host: String,
+        port: u16,
+        username: Option<String>,
+        password: Option<String>,
+        /// `starttls`, `tls` or `none` (plaintext, local relays only)
+        tls: String,
+    },
+    /// Append emails to a file and the log instead of sending them
+    Log { path: String },
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self {
+            access_token_ttl: Duration::minutes(env_or("ACCESS_TOKEN_TTL_MINUTES", 15)),
+            refresh_token_ttl: Duration::days(env_or("REFRESH_TOKEN_TTL_DAYS", 30)),
+            jwt_key_grace: Duration::minutes(env_or("JWT_KEY_GRACE_MINUTES", 60)),
+            jwt_algorithm: env_or("JWT_ALGORITHM", Algorithm::HS256),
+            trust_proxy_headers: env_or("TRUST_PROXY_HEADERS", false),
+            discord: DiscordConfig::from_env(),
+            app_url: env::var("APP_URL")
+                .unwrap_or_else(|_| "http://localhost:3000".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            password_reset_ttl: Duration::minutes(env_or("PASSWORD_RESET_TTL_MINUTES", 60)),
+            email_verification_ttl: Duration::hours(env_or("EMAIL_VERIFICATION_TTL_HOURS", 48)),
+            require_verified_email: env_or("REQUIRE_VERIFIED_EMAIL", true),
+            mfa_challenge_ttl: Duration::minutes(env_or("MFA_CHALLENGE_TTL_MINUTES", 5)),
+            require_totp_for_high_value: env_or("REQUIRE_TOTP_FOR_HIGH_VALUE", false),
+            account_deletion_grace: Duration::days(env_or("ACCOUNT_DELETION_GRACE_DAYS", 30)),
+            signature_max_skew: Duration::seconds(env_or("SIGNATURE_MAX_SKEW_SECONDS", 300)),
+            game_link_code_ttl: Duration::minutes(env_or("GAME_LINK_CODE_TTL_MINUTES", 10)),
+            password_policy: PasswordPolicyConfig::from_env(),
+            login_throttle: LoginThrottleConfig::from_env(),
+            game_link_throttle: LoginThrottleConfig::game_link_from_env(),
+            mail: MailConfig::from_env(),
+        }
+    }
+}
+
+impl PasswordPolicyConfig {
+    fn from_env() -> Self {
+        Self {
+            memory_kib: env_or("ARGON2_MEMORY_KIB", 19456),
+            iterations: env_or("ARGON2_ITERATIONS", 2),
+            parallelism: env_or("ARGON2_PARALLELISM", 1),
+            pepper: env_or("PASSWORD_PEPPER", false),
+        }
+    }
+}
+
+impl LoginThrottleConfig {
+    fn from_env() -> Self {
+        Self {
+            max_account_failures: env_or("LOGIN_MAX_ACCOUNT_FAILURES", 5),
+            max_ip_failures: env_or("LOGIN_MAX_IP_FAILURES", 30),
+            window: Duration::minutes(env_or("LOGIN_FAILURE_WINDOW_MINUTES", 60)),
+            base_delay: Duration::seconds(env_or("LOGIN_LOCKOUT_BASE_SECONDS", 30)),
+            max_delay: Duration::minutes(env_or("LOGIN_LOCKOUT_MAX_MINUTES", 30)),
+        }
+    }
+
+    fn game_link_from_env() -> Self {
+        Self {
+            max_account_failures: env_or("GAME_LINK_MAX_USER_FAILURES", 5),
+            max_ip_failures: env_or("GAME_LINK_MAX_IP_FAILURES", 20),
+            window: Duration::minutes(env_or("GAME_LINK_FAILURE_WINDOW_MINUTES", 60)),
+            base_delay: Duration::seconds(env_or("GAME_LINK_LOCKOUT_BASE_SECONDS", 60)),
+            max_delay: Duration::minutes(env_or("GAME_LINK_LOCKOUT_MAX_MINUTES", 60)),
+        }
+    }
+}
+
+impl MailConfig {
+    fn from_env() -> Self {
+        let from =
+            env::var("MAIL_FROM").unwrap_or_else(|_| "Bridge <noreply@localhost>".to_string());
+
+        let transport = match env::var("MAILER").as_deref() {
+            Ok("smtp") => MailTransport::Smtp {
+                host: env::var("SMTP_HOST").unwrap_or_else(|_| "localhost".to_string()),
+                port: env_or("SMTP_PORT", 587),
+                username: env::var("SMTP_USERNAME").ok(),
+                password: env::var("SMTP_PASSWORD").ok(),
+                tls: env::var("SMTP_TLS").unwrap_or_else(|_| "starttls".to_string()),
+            },
+            _ => MailTransport::Log {
+                path: env::var("MAIL_LOG_PATH").unwrap_or_else(|_| "logs/mail.log".to_string()),
+            },
+        };
+
+        Self { from, transport }
+    }
+}
+
+impl DiscordConfig {
+    fn from_env() -> Option<Self> {
+        let client_id = env::var("DISCORD_CLIENT_ID").ok()?;
+
+        Some(Self {
+            client_id,
+            client_secret: env::var("DISCORD_CLIENT_SECRET").unwrap_or_default(),
+            redirect_url: env::var("DISCORD_REDIRECT_URL").unwrap_or_else(|_| {
+                "http://localhost:3000/api/v1/auth/discord/callback".to_string()
+            }),
+            auth_url: env::var("DISCORD_AUTH_URL")
+                .unwrap_or_else(|_| "https://discord.com/oauth2/authorize".to_string()),
+            token_url: env::var("DISCORD_TOKEN_URL")
+                .unwrap_or_else(|_| "https://discord.com/api/oauth2/token".to_string()),
+            api_url: env::var("DISCORD_API_URL")
+                .unwrap_or_else(|_| "https://discord.com/api".to_string()),
+        })
+    }
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    match env::var(key) {
+        Ok(value) => value.parse().unwrap_or_else(|_| {
+            tracing::warn!("Invalid value for {}, using default", key);
+            default
+        }),
+        Err(_) => default,
+    }
+}
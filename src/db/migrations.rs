@@ -0,0 +1,706 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, MySqlConnection};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// Suffix used for the paired rollback script of a migration,
+/// e.g. `002_user_sessions.sql` is reverted by `002_user_sessions.down.sql`
+const DOWN_SUFFIX: &str = ".down.sql";
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid migration file {0}: {1}")]
+    InvalidFile(String, String),
+
+    #[error("Duplicate migration version {0}")]
+    DuplicateVersion(i64),
+
+    #[error(
+        "Migration {0} has been modified after it was applied (expected checksum {1}, found {2})"
+    )]
+    ChecksumMismatch(String, String, String),
+
+    #[error("Migration {0} has been applied but its file is missing")]
+    MissingFile(i64),
+
+    #[error("Migration {0} has no down script")]
+    MissingDownScript(String),
+
+    #[error("Migration {0} failed at statement {1}: {2}")]
+    StatementFailed(String, usize, sqlx::Error),
+}
+
+pub type Result<T> = std::result::Result<T, MigrationError>;
+
+/// A migration file pair loaded from disk
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: i64,
+    pub name: String,
+    pub up_sql: String,
+    pub down_sql: Option<String>,
+    pub checksum: String,
+}
+
+/// A row of the `schema_migrations` tracking table
+#[derive(Debug, Clone, FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: DateTime<Utc>,
+    pub execution_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    Modified,
+    Missing,
+}
+
+impl std::fmt::Display for MigrationState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "MODIFIED",
+            MigrationState::Missing => "MISSING",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+impl Migration {
+    pub fn filename(&self) -> String {
+        format!("{:03}_{}.sql", self.version, self.name)
+    }
+}
+
+pub fn checksum(sql: &str) -> String {
+    hex::encode(Sha256::digest(sql.as_bytes()))
+}
+
+/// Load all migrations from a directory, sorted by version
+pub fn load_migrations(dir: &Path) -> Result<Vec<Migration>> {
+    let mut ups: HashMap<i64, (String, String)> = HashMap::new();
+    let mut downs: HashMap<i64, String> = HashMap::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let filename = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if name.ends_with(".sql") => name.to_string(),
+            _ => continue,
+        };
+
+        let (stem, is_down) = match filename.strip_suffix(DOWN_SUFFIX) {
+            Some(stem) => (stem.to_string(), true),
+            None => (filename.trim_end_matches(".sql").to_string(), false),
+        };
+
+        let (version, name) = parse_stem(&stem).ok_or_else(|| {
+            MigrationError::InvalidFile(
+                filename.clone(),
+                "expected a name like 001_description.sql".to_string(),
+            )
+        })?;
+
+        let sql = fs::read_to_string(&path)?;
+
+        if is_down {
+            downs.insert(version, sql);
+        } else if ups.insert(version, (name, sql)).is_some() {
+            return Err(MigrationError::DuplicateVersion(version));
+        }
+    }
+
+    if let Some(version) = downs.keys().find(|version| !ups.contains_key(version)) {
+        return Err(MigrationError::InvalidFile(
+            format!("{:03}{}", version, DOWN_SUFFIX),
+            "down script has no matching up script".to_string(),
+        ));
+    }
+
+    let mut migrations: Vec<Migration> = ups
+        .into_iter()
+        .map(|(version, (name, up_sql))| Migration {
+            version,
+            checksum: checksum(&up_sql),
+            down_sql: downs.remove(&version),
+            name,
+            up_sql,
+        })
+        .collect();
+
+    migrations.sort_by_key(|migration| migration.version);
+    Ok(migrations)
+}
+
+fn parse_stem(stem: &str) -> Option<(i64, String)> {
+    let (version, name) = stem.split_once('_')?;
+    let version = version.parse().ok()?;
+    if name.is_empty() {
+        return None;
+    }
+    Some((version, name.to_string()))
+}
+
+/// Split a SQL script into individual statements, ignoring comment-only lines
+/// and semicolons inside string literals
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current_statement = String::new();
+    let mut in_string = false;
+    let mut string_delimiter = ' ';
+
+    for line in sql.lines() {
+        let trimmed = line.trim();
+
+        // Skip comment-only lines
+        if trimmed.starts_with("--") || trimmed.is_empty() {
+            continue;
+        }
+
+        // Track string literals to avoid splitting on semicolons inside strings
+        let mut chars = line.char_indices().peekable();
+        while let Some((i, ch)) = chars.next() {
+            if in_string {
+                // Backslash escapes and doubled delimiters don't end the string
+                if ch == '\\'
+                    || (ch == string_delimiter && chars.peek().map(|(_, c)| *c) == Some(ch))
+                {
+                    current_statement.push(ch);
+                    if let Some((_, escaped)) = chars.next() {
+                        current_statement.push(escaped);
+                    }
+                    continue;
+                }
+                if ch == string_delimiter {
+                    in_string = false;
+                }
+            } else if is_comment_start(&line[i..]) {
+                // Trailing comment; its quotes and semicolons don't count
+                break;
+            } else if ch == '\'' || ch == '"' {
+                in_string = true;
+                string_delimiter = ch;
+            }
+
+            current_statement.push(ch);
+
+            if ch == ';' && !in_string {
+                let stmt = current_statement.trim().to_string();
+                if !stmt.is_empty() {
+                    statements.push(stmt);
+                }
+                current_statement.clear();
+            }
+        }
+        current_statement.push('\n');
+    }
+
+    // Add any remaining statement
+    let remaining = current_statement.trim();
+    if !remaining.is_empty() && !remaining.starts_with("--") {
+        statements.push(remaining.to_string());
+    }
+
+    statements
+}
+
+/// `--` starts a comment only when followed by whitespace or the end of the line
+fn is_comment_start(rest: &str) -> bool {
+    rest.strip_prefix("--")
+        .is_some_and(|after| after.is_empty() || after.starts_with(char::is_whitespace))
+}
+
+/// Create the tracking table if it doesn't exist yet
+pub async fn ensure_migrations_table(conn: &mut MySqlConnection) -> Result<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            name VARCHAR(255) NOT NULL,
+            checksum CHAR(64) NOT NULL,
+            applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            execution_ms BIGINT NOT NULL DEFAULT 0
+        ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Fetch applied migrations, treating a missing tracking table as an empty history
+pub async fn applied_migrations(conn: &mut MySqlConnection) -> Result<Vec<AppliedMigration>> {
+    let exists: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM information_schema.tables
+         WHERE table_schema = DATABASE() AND table_name = 'schema_migrations'",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if exists.0 == 0 {
+        return Ok(Vec::new());
+    }
+
+    let applied = sqlx::query_as(
+        "SELECT version, name, checksum, applied_at, execution_ms
+         FROM schema_migrations ORDER BY version",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(applied)
+}
+
+/// Compare migrations on disk against the tracking table
+pub fn status(migrations: &[Migration], applied: &[AppliedMigration]) -> Vec<MigrationStatus> {
+    let applied_by_version: HashMap<i64, &AppliedMigration> =
+        applied.iter().map(|row| (row.version, row)).collect();
+
+    let mut statuses: Vec<MigrationStatus> = migrations
+        .iter()
+        .map(
+            |migration| match applied_by_version.get(&migration.version) {
+                Some(row) => MigrationStatus {
+                    version: migration.version,
+                    name: migration.name.clone(),
+                    state: if row.checksum == migration.checksum {
+                        MigrationState::Applied
+                    } else {
+                        MigrationState::Modified
+                    },
+                    applied_at: Some(row.applied_at),
+                },
+                None => MigrationStatus {
+                    version: migration.version,
+                    name: migration.name.clone(),
+                    state: MigrationState::Pending,
+                    applied_at: None,
+                },
+            },
+        )
+        .collect();
+
+    for row in applied {
+        if !migrations.iter().any(|m| m.version == row.version) {
+            statuses.push(MigrationStatus {
+                version: row.version,
+                name: row.name.clone(),
+                state: MigrationState::Missing,
+                applied_at: Some(row.applied_at),
+            });
+        }
+    }
+
+    statuses.sort_by_key(|status| status.version);
+    statuses
+}
+
+/// Refuse to continue when applied migrations were edited or deleted on disk
+pub fn verify_applied(migrations: &[Migration], applied: &[AppliedMigration]) -> Result<()> {
+    for row in applied {
+        let migration = migrations
+            .iter()
+            .find(|m| m.version == row.version)
+            .ok_or(MigrationError::MissingFile(row.version))?;
+
+        if migration.checksum != row.checksum {
+            return Err(MigrationError::ChecksumMismatch(
+                migration.filename(),
+                row.checksum.clone(),
+                migration.checksum.clone(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Migrations that exist on disk but have not been applied, in order
+pub fn pending<'a>(
+    migrations: &'a [Migration],
+    applied: &[AppliedMigration],
+) -> Vec<&'a Migration> {
+    migrations
+        .iter()
+        .filter(|m| !applied.iter().any(|row| row.version == m.version))
+        .collect()
+}
+
+/// The last `count` applied migrations, newest first
+pub fn to_revert<'a>(
+    migrations: &'a [Migration],
+    applied: &[AppliedMigration],
+    count: usize,
+) -> Result<Vec<&'a Migration>> {
+    applied
+        .iter()
+        .rev()
+        .take(count)
+        .map(|row| {
+            migrations
+                .iter()
+                .find(|m| m.version == row.version)
+                .ok_or(MigrationError::MissingFile(row.version))
+        })
+        .collect()
+}
+
+/// Run a migration's up script and record it in the tracking table
+pub async fn apply(conn: &mut MySqlConnection, migration: &Migration) -> Result<usize> {
+    let started = Instant::now();
+    let executed = execute_script(conn, &migration.filename(), &migration.up_sql).await?;
+
+    sqlx::query(
+        "INSERT INTO schema_migrations (version, name, checksum, execution_ms) VALUES (?, ?, ?, ?)",
+    )
+    .bind(migration.version)
+    .bind(&migration.name)
+    .bind(&migration.checksum)
+    .bind(started.elapsed().as_millis() as i64)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(executed)
+}
+
+/// Run a migration's down script and remove it from the tracking table
+pub async fn revert(conn: &mut MySqlConnection, migration: &Migration) -> Result<usize> {
+    let down_sql = migration
+        .down_sql
+        .as_ref()
+        .ok_or_else(|| MigrationError::MissingDownScript(migration.filename()))?;
+
+    let executed = execute_script(conn, &migration.filename(), down_sql).await?;
+
+    sqlx::query("DELETE FROM schema_migrations WHERE version = ?")
+        .bind(migration.version)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(executed)
+}
+
+async fn execute_script(conn: &mut MySqlConnection, filename: &str, sql: &str) -> Result<usize> {
+    let statements = split_statements(sql);
+
+    // Disable foreign key checks so scripts can create and drop tables in any order
+    sqlx::query("SET FOREIGN_KEY_CHECKS = 0")
+        .execute(&mut *conn)
+        .await?;
+
+    for (i, statement) in statements.iter().enumerate() {
+        if let Err(e) = sqlx::query(statement).execute(&mut *conn).await {
+            sqlx::query("SET FOREIGN_KEY_CHECKS = 1")
+                .execute(&mut *conn)
+                .await
+                .ok();
+
+            return Err(MigrationError::StatementFailed(
+                filename.to_string(),
+                i + 1,
+                e,
+            ));
+        }
+    }
+
+    sqlx::query("SET FOREIGN_KEY_CHECKS = 1")
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(statements.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migration(version: i64, up_sql: &str) -> Migration {
+        Migration {
+            version,
+            name: format!("migration_{}", version),
+            up_sql: up_sql.to_string(),
+            down_sql: Some(format!("DROP TABLE t{};", version)),
+            checksum: checksum(up_sql),
+        }
+    }
+
+    fn applied(migration: &Migration) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version,
+            name: migration.name.clone(),
+            checksum: migration.checksum.clone(),
+            applied_at: Utc::now(),
+            execution_ms: 0,
+        }
+    }
+
+    /// A scratch migrations directory, removed when dropped
+    struct TempDir(std::path::PathBuf);
+
+    impl TempDir {
+        fn with_files(files: &[(&str, &str)]) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("bridge-migrations-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&dir).unwrap();
+            for (name, contents) in files {
+                fs::write(dir.join(name), contents).unwrap();
+            }
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    #[test]
+    fn split_statements_skips_comment_lines_and_blank_lines() {
+        let sql = "-- Create a table\n\nCREATE TABLE a (id INT);\n-- another\nDROP TABLE b;\n";
+
+        assert_eq!(
+            split_statements(sql),
+            vec!["CREATE TABLE a (id INT);", "DROP TABLE b;"]
+        );
+    }
+
+    #[test]
+    fn split_statements_keeps_semicolons_inside_strings() {
+        let sql = "INSERT INTO t (v) VALUES ('a;b'), (\"c;d\");\nSELECT 1;";
+
+        assert_eq!(
+            split_statements(sql),
+            vec!["INSERT INTO t (v) VALUES ('a;b'), (\"c;d\");", "SELECT 1;"]
+        );
+    }
+
+    #[test]
+    fn split_statements_handles_escaped_quotes_and_multiline_statements() {
+        let sql = "INSERT INTO p (d) VALUES\n('Read any player''s inventory');\nSELECT 2;";
+
+        assert_eq!(
+            split_statements(sql),
+            vec![
+                "INSERT INTO p (d) VALUES\n('Read any player''s inventory');",
+                "SELECT 2;"
+            ]
+        );
+    }
+
+    #[test]
+    fn split_statements_handles_backslash_escaped_quotes() {
+        let sql = "INSERT INTO t (v) VALUES ('it\\'s; fine');\nSELECT 3;";
+
+        assert_eq!(
+            split_statements(sql),
+            vec!["INSERT INTO t (v) VALUES ('it\\'s; fine');", "SELECT 3;"]
+        );
+    }
+
+    #[test]
+    fn split_statements_ignores_quotes_in_trailing_comments() {
+        let sql = "CREATE TABLE a (\n    id INT, -- the player's id\n    name TEXT\n);\nDROP TABLE b; -- don't; really\nSELECT 4;";
+
+        assert_eq!(
+            split_statements(sql),
+            vec![
+                "CREATE TABLE a (\n    id INT, \n    name TEXT\n);",
+                "DROP TABLE b;",
+                "SELECT 4;"
+            ]
+        );
+    }
+
+    #[test]
+    fn split_statements_treats_double_dash_without_space_as_sql() {
+        assert_eq!(
+            split_statements("SELECT 5--1;\nSELECT 6;"),
+            vec!["SELECT 5--1;", "SELECT 6;"]
+        );
+    }
+
+    #[test]
+    fn split_statements_keeps_a_trailing_statement_without_semicolon() {
+        assert_eq!(
+            split_statements("SELECT 1;\nSELECT 2"),
+            vec!["SELECT 1;", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn parse_stem_reads_version_and_name() {
+        assert_eq!(
+            parse_stem("002_session_refresh_tokens"),
+            Some((2, "session_refresh_tokens".to_string()))
+        );
+        assert_eq!(parse_stem("002_"), None);
+        assert_eq!(parse_stem("abc_name"), None);
+        assert_eq!(parse_stem("002"), None);
+    }
+
+    #[test]
+    fn filename_pads_the_version() {
+        assert_eq!(migration(7, "").filename(), "007_migration_7.sql");
+    }
+
+    #[test]
+    fn load_migrations_pairs_down_scripts_and_sorts_by_version() {
+        let dir = TempDir::with_files(&[
+            ("002_second.sql", "CREATE TABLE b (id INT);"),
+            ("001_first.sql", "CREATE TABLE a (id INT);"),
+            ("001_first.down.sql", "DROP TABLE a;"),
+            ("README.md", "ignored"),
+        ]);
+
+        let migrations = load_migrations(&dir.0).unwrap();
+
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(migrations[0].version, 1);
+        assert_eq!(migrations[0].name, "first");
+        assert_eq!(migrations[0].down_sql.as_deref(), Some("DROP TABLE a;"));
+        assert_eq!(migrations[0].checksum, checksum("CREATE TABLE a (id INT);"));
+        assert_eq!(migrations[1].version, 2);
+        assert!(migrations[1].down_sql.is_none());
+    }
+
+    #[test]
+    fn load_migrations_rejects_bad_names_duplicates_and_orphan_down_scripts() {
+        let bad_name = TempDir::with_files(&[("first.sql", "SELECT 1;")]);
+        assert!(matches!(
+            load_migrations(&bad_name.0),
+            Err(MigrationError::InvalidFile(..))
+        ));
+
+        let duplicate =
+            TempDir::with_files(&[("001_a.sql", "SELECT 1;"), ("001_b.sql", "SELECT 2;")]);
+        assert!(matches!(
+            load_migrations(&duplicate.0),
+            Err(MigrationError::DuplicateVersion(1))
+        ));
+
+        let orphan =
+            TempDir::with_files(&[("001_a.sql", "SELECT 1;"), ("002_b.down.sql", "SELECT 2;")]);
+        assert!(matches!(
+            load_migrations(&orphan.0),
+            Err(MigrationError::InvalidFile(..))
+        ));
+    }
+
+    #[test]
+    fn verify_applied_detects_edited_and_deleted_migrations() {
+        let first = migration(1, "CREATE TABLE a (id INT);");
+        let second = migration(2, "CREATE TABLE b (id INT);");
+        let history = vec![applied(&first), applied(&second)];
+
+        assert!(verify_applied(&[first.clone(), second.clone()], &history).is_ok());
+
+        let edited = migration(2, "CREATE TABLE b (id BIGINT);");
+        assert!(matches!(
+            verify_applied(&[first.clone(), edited.clone()], &history),
+            Err(MigrationError::ChecksumMismatch(..))
+        ));
+        assert_eq!(
+            status(&[first.clone(), edited], &history)[1].state,
+            MigrationState::Modified
+        );
+
+        assert!(matches!(
+            verify_applied(std::slice::from_ref(&first), &history),
+            Err(MigrationError::MissingFile(2))
+        ));
+        assert_eq!(status(&[first], &history)[1].state, MigrationState::Missing);
+    }
+
+    #[test]
+    fn pending_lists_unapplied_migrations_in_order() {
+        let migrations = vec![migration(1, "a"), migration(2, "b"), migration(3, "c")];
+        let history = vec![applied(&migrations[0])];
+
+        let versions: Vec<i64> = pending(&migrations, &history)
+            .iter()
+            .map(|m| m.version)
+            .collect();
+
+        assert_eq!(versions, vec![2, 3]);
+        assert_eq!(
+            status(&migrations, &history)
+                .iter()
+                .map(|s| s.state.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                MigrationState::Applied,
+                MigrationState::Pending,
+                MigrationState::Pending
+            ]
+        );
+    }
+
+    #[test]
+    fn to_revert_takes_the_newest_applied_migrations_first() {
+        let migrations = vec![migration(1, "a"), migration(2, "b"), migration(3, "c")];
+        let history = vec![applied(&migrations[0]), applied(&migrations[1])];
+
+        let versions = |count| -> Vec<i64> {
+            to_revert(&migrations, &history, count)
+                .unwrap()
+                .iter()
+                .map(|m| m.version)
+                .collect()
+        };
+
+        // Never the pending migration 3, and never more than was applied
+        assert_eq!(versions(1), vec![2]);
+        assert_eq!(versions(2), vec![2, 1]);
+        assert_eq!(versions(10), vec![2, 1]);
+        assert!(versions(0).is_empty());
+    }
+
+    #[test]
+    fn to_revert_fails_when_an_applied_file_is_missing() {
+        let migrations = vec![migration(1, "a")];
+        let history = vec![applied(&migrations[0]), applied(&migration(2, "b"))];
+
+        assert!(matches!(
+            to_revert(&migrations, &history, 1),
+            Err(MigrationError::MissingFile(2))
+        ));
+    }
+
+    #[test]
+    fn repository_migrations_load_and_all_have_down_scripts() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations");
+        let migrations = load_migrations(&dir).unwrap();
+
+        assert!(!migrations.is_empty());
+        for migration in &migrations {
+            assert!(
+                migration.down_sql.is_some(),
+                "{} has no down script",
+                migration.filename()
+            );
+            assert!(!split_statements(&migration.up_sql).is_empty());
+        }
+    }
+}
@@ -1 +1 @@
-// Database module - Query functions will be implemented here
+pub mod migrations;
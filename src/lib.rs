@@ -1,5 +1,6 @@
 pub mod api;
 pub mod auth;
+pub mod config;
 pub mod db;
 pub mod models;
 pub mod services;
@@ -7,7 +8,13 @@ pub mod utils;
 
 pub struct AppState {
     pub db: sqlx::MySqlPool,
-    pub jwt_secret: String,
+    pub signing_keys: utils::KeyRing,
+    pub passwords: utils::PasswordHasher,
+    /// Nonces of recently verified signed requests
+    pub nonces: utils::NonceCache,
+    pub config: config::Config,
+    pub identity_providers: auth::ProviderRegistry,
+    pub mailer: std::sync::Arc<dyn services::mailer::Mailer>,
 }
 
-pub use utils::AppError;
\ No newline at end of file
+pub use utils::AppError;
@@ -1,4 +1,11 @@
-use bridge::{api, utils, AppState};
+use bridge::{
+    api,
+    auth::{discord::DiscordProvider, ProviderRegistry},
+    config::Config,
+    services::{accounts, mailer},
+    utils::{self, KeyRing, NonceCache, PasswordHasher},
+    AppState,
+};
 
 use axum::{
     http::{header, Method},
@@ -32,27 +39,63 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .connect(&database_url)
         .await?;
 
-    // Get JWT secret from database or generate one
-    let jwt_secret = utils::secrets::get_or_create_secret(&db_pool, "jwt_secret").await?;
+    let config = Config::from_env();
+
+    // Load JWT signing keys from the database, generating the first one if needed
+    let signing_keys =
+        KeyRing::load(db_pool.clone(), config.jwt_key_grace, config.jwt_algorithm).await?;
+
+    // Password hashing policy, including the pepper when enabled
+    let passwords = PasswordHasher::load(&db_pool, &config.password_policy).await?;
+
+    // Register the identity providers that have been configured
+    let mut identity_providers = ProviderRegistry::new();
+    match &config.discord {
+        Some(discord) => identity_providers.register(DiscordProvider::new(discord)?),
+        None => tracing::info!("DISCORD_CLIENT_ID not set, Discord login disabled"),
+    }
+    tracing::info!(
+        "Identity providers enabled: {:?}",
+        identity_providers.names()
+    );
+
+    let mailer = mailer::from_config(&config.mail)?;
+
+    // Permanently remove deleted accounts once their grace period is over
+    accounts::spawn_purge_job(db_pool.clone(), config.account_deletion_grace);
 
     // Create application state
     let app_state = Arc::new(AppState {
         db: db_pool,
-        jwt_secret,
+        signing_keys,
+        passwords,
+        nonces: NonceCache::new(),
+        config,
+        identity_providers,
+        mailer,
     });
 
     // Configure CORS
     let cors = CorsLayer::new()
         .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
         .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
 
     // Build the application router
-    let app = Router::new().nest("/api/v1", api::routes(app_state)).layer(
-        ServiceBuilder::new()
-            .layer(TraceLayer::new_for_http())
-            .layer(cors),
-    );
+    let app = Router::new()
+        .merge(api::well_known_routes(app_state.clone()))
+        .nest("/api/v1", api::routes(app_state))
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(cors),
+        );
 
     // Create the server address
     let addr: SocketAddr = format!("{}:{}", host, port).parse()?;
@@ -60,7 +103,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Start the server
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
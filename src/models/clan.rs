@@ -36,13 +36,14 @@ impl From<String> for ClanRank {
     }
 }
 
-impl ToString for ClanRank {
-    fn to_string(&self) -> String {
-        match self {
-            ClanRank::Leader => "Leader".to_string(),
-            ClanRank::Officer => "Officer".to_string(),
-            ClanRank::Member => "Member".to_string(),
-        }
+impl std::fmt::Display for ClanRank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ClanRank::Leader => "Leader",
+            ClanRank::Officer => "Officer",
+            ClanRank::Member => "Member",
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -96,6 +97,15 @@ pub struct UpdateClanRequest {
     pub max_members: Option<i32>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ClanInvite {
+    pub id: String,
+    pub clan_id: String,
+    pub user_id: String,
+    pub invited_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InviteMemberRequest {
     pub user_id: String,
@@ -107,6 +117,11 @@ pub struct UpdateMemberRankRequest {
     pub rank: ClanRank,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferLeadershipRequest {
+    pub user_id: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DepositItemRequest {
     pub user_item_id: String,
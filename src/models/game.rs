@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+/// A game in the catalog. `name` is the identifier used everywhere else, e.g.
+/// `user_games.game_name` and game client allowed games.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Game {
+    pub id: i32,
+    pub name: String,
+    pub display_name: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateGameRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1, max = 255))]
+    pub display_name: String,
+}
+
+/// Body of `POST /games/link-codes`, sent by a game server for a player in game
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateLinkCodeRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub game_name: String,
+    /// The player's account ID within the game
+    #[validate(length(min = 1, max = 255))]
+    pub game_user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCodeResponse {
+    /// Shown to the player in game, who enters it on Bridge
+    pub code: String,
+    pub game_name: String,
+    pub game_user_id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Body of `POST /users/me/games`
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct LinkGameRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub code: String,
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+/// Allowed-games entry that lets a client act for players of any game
+pub const ANY_GAME: &str = "*";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GameClient {
+    pub id: String,
+    pub name: String,
+    pub key_prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    #[serde(skip_serializing)]
+    pub signing_secret: Option<String>,
+    pub scopes: String,        // space separated
+    pub allowed_games: String, // space separated
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// A game client as shown to admins; the key itself is never returned again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameClientInfo {
+    pub id: String,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub allowed_games: Vec<String>,
+    /// Whether the client has a secret for signing requests
+    pub signing_enabled: bool,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateGameClientRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub scopes: Vec<String>,
+    /// Game names whose players the client may act for; `["*"]` allows any game
+    #[validate(length(min = 1))]
+    pub allowed_games: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateGameClientResponse {
+    pub client: GameClientInfo,
+    /// Shown once; send it in the `X-Bridge-Key` header
+    pub api_key: String,
+    /// Shown once; used to sign requests sent with `X-Bridge-Key-Id`
+    pub signing_secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningSecretResponse {
+    /// Value for the `X-Bridge-Key-Id` header
+    pub key_id: String,
+    /// Shown once; replaces the previous secret immediately
+    pub signing_secret: String,
+}
+
+impl GameClient {
+    pub fn scope_list(&self) -> Vec<String> {
+        split_list(&self.scopes)
+    }
+
+    pub fn allowed_game_list(&self) -> Vec<String> {
+        split_list(&self.allowed_games)
+    }
+
+    pub fn to_info(&self) -> GameClientInfo {
+        GameClientInfo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            key_prefix: self.key_prefix.clone(),
+            scopes: self.scope_list(),
+            allowed_games: self.allowed_game_list(),
+            signing_enabled: self.signing_secret.is_some(),
+            created_by: self.created_by.clone(),
+            created_at: self.created_at,
+            last_used_at: self.last_used_at,
+            revoked_at: self.revoked_at,
+        }
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value.split_whitespace().map(str::to_string).collect()
+}
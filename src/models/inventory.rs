@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ItemRarity {
@@ -35,6 +36,10 @@ pub struct ItemModifier {
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct InventoryItem {
     pub id: i32,
+    /// Owning game; `None` for Bridge-wide items
+    pub game_id: Option<i32>,
+    /// Unique within the owning game
+    pub item_key: String,
     pub name: String,
     pub description: Option<String>,
     pub item_type: String,
@@ -87,17 +92,55 @@ pub struct AppliedModifier {
     pub applied_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateItemRequest {
+    /// Owning game's name; omit for a Bridge-wide item
+    pub game: Option<String>,
+    /// Lowercase letters, digits, `-`, `_` and `.`; unique within the game
+    #[validate(length(min = 1, max = 100))]
+    pub item_key: String,
+    #[validate(length(min = 1, max = 255))]
     pub name: String,
     pub description: Option<String>,
+    #[validate(length(min = 1, max = 50))]
     pub item_type: String,
     pub rarity_id: i32,
+    #[validate(range(min = 0))]
     pub base_credit_value: i32,
+    #[validate(range(min = 0, max = 10))]
     pub max_modifiers: i32,
+    #[validate(url, length(max = 500))]
     pub icon_url: Option<String>,
 }
 
+/// Query parameters shared by the item catalog and inventory endpoints; all
+/// optional and combined with AND
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ItemFilter {
+    /// Only items owned by this game
+    pub game: Option<String>,
+    pub item_type: Option<String>,
+    /// Only items that can be used in this game
+    pub usable_in: Option<String>,
+    /// Only items that can be shown in this game
+    pub shown_in: Option<String>,
+}
+
+/// Whether an item may be used or shown in a game other than its own
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ItemGameRule {
+    pub game_name: String,
+    pub can_use: bool,
+    pub can_show: bool,
+}
+
+/// Body of `PUT /inventory/items/:id/games/:game_name`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetItemGameRuleRequest {
+    pub can_use: bool,
+    pub can_show: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GrantItemRequest {
     pub user_id: String,
@@ -20,12 +20,13 @@ impl From<String> for ListingType {
     }
 }
 
-impl ToString for ListingType {
-    fn to_string(&self) -> String {
-        match self {
-            ListingType::Fixed => "fixed".to_string(),
-            ListingType::Auction => "auction".to_string(),
-        }
+impl std::fmt::Display for ListingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ListingType::Fixed => "fixed",
+            ListingType::Auction => "auction",
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -45,12 +46,13 @@ impl From<String> for ListingVisibility {
     }
 }
 
-impl ToString for ListingVisibility {
-    fn to_string(&self) -> String {
-        match self {
-            ListingVisibility::Public => "public".to_string(),
-            ListingVisibility::ClanOnly => "clan_only".to_string(),
-        }
+impl std::fmt::Display for ListingVisibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ListingVisibility::Public => "public",
+            ListingVisibility::ClanOnly => "clan_only",
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -74,14 +76,15 @@ impl From<String> for ListingStatus {
     }
 }
 
-impl ToString for ListingStatus {
-    fn to_string(&self) -> String {
-        match self {
-            ListingStatus::Active => "active".to_string(),
-            ListingStatus::Sold => "sold".to_string(),
-            ListingStatus::Cancelled => "cancelled".to_string(),
-            ListingStatus::Expired => "expired".to_string(),
-        }
+impl std::fmt::Display for ListingStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ListingStatus::Active => "active",
+            ListingStatus::Sold => "sold",
+            ListingStatus::Cancelled => "cancelled",
+            ListingStatus::Expired => "expired",
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -145,6 +148,10 @@ pub struct PlaceBidRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketplaceFilter {
+    /// Only items owned by this game
+    pub game: Option<String>,
+    /// Only items that can be used in this game
+    pub usable_in: Option<String>,
     pub item_type: Option<String>,
     pub rarity_id: Option<i32>,
     pub min_price: Option<i32>,
@@ -153,6 +160,38 @@ pub struct MarketplaceFilter {
     pub visibility: Option<ListingVisibility>,
     pub clan_id: Option<String>,
     pub search_term: Option<String>,
+    pub page: Option<i64>,     // starts at 1
+    pub per_page: Option<i64>, // defaults to 20, at most 50
+}
+
+/// A listing with the item details needed to browse the marketplace
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MarketplaceListingSummary {
+    pub id: String,
+    pub seller_id: String,
+    pub seller_username: String,
+    pub user_item_id: String,
+    pub item_id: i32,
+    pub item_name: String,
+    pub item_type: String,
+    pub rarity_id: i32,
+    /// Owning game's name; `None` for Bridge-wide items
+    pub game: Option<String>,
+    pub price: i32,
+    pub listing_type: String,
+    pub visibility: String,
+    pub clan_id: Option<String>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceListingsResponse {
+    pub results: Vec<MarketplaceListingSummary>,
+    pub page: i64,
+    pub per_page: i64,
+    pub has_more: bool,
 }
 
 impl MarketplaceListing {
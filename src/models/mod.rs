@@ -1,13 +1,27 @@
 pub mod clan;
+pub mod game;
+pub mod game_client;
 pub mod inventory;
 pub mod marketplace;
 pub mod message;
+pub mod mfa;
+pub mod oauth;
+pub mod profile;
+pub mod role;
+pub mod social;
 pub mod trade;
 pub mod user;
 
 pub use clan::*;
+pub use game::*;
+pub use game_client::*;
 pub use inventory::*;
 pub use marketplace::*;
 pub use message::*;
+pub use mfa::*;
+pub use oauth::*;
+pub use profile::*;
+pub use role::*;
+pub use social::*;
 pub use trade::*;
 pub use user::*;
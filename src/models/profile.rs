@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Who can see a section of a public profile. The owner always sees everything.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileVisibility {
+    Everyone,
+    Friends,
+    Clan,
+    FriendsAndClan,
+    Private,
+}
+
+impl From<String> for ProfileVisibility {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "everyone" => ProfileVisibility::Everyone,
+            "friends" => ProfileVisibility::Friends,
+            "clan" => ProfileVisibility::Clan,
+            "friends_and_clan" => ProfileVisibility::FriendsAndClan,
+            _ => ProfileVisibility::Private,
+        }
+    }
+}
+
+impl std::fmt::Display for ProfileVisibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ProfileVisibility::Everyone => "everyone",
+            ProfileVisibility::Friends => "friends",
+            ProfileVisibility::Clan => "clan",
+            ProfileVisibility::FriendsAndClan => "friends_and_clan",
+            ProfileVisibility::Private => "private",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+/// Row of `user_privacy_settings`; also the body of `PUT /users/me/privacy`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacySettings {
+    pub clan: ProfileVisibility,
+    /// Linked game accounts
+    pub games: ProfileVisibility,
+    /// Equipped items
+    pub showcase: ProfileVisibility,
+}
+
+impl Default for PrivacySettings {
+    /// Matches the column defaults of `user_privacy_settings`
+    fn default() -> Self {
+        Self {
+            clan: ProfileVisibility::Everyone,
+            games: ProfileVisibility::FriendsAndClan,
+            showcase: ProfileVisibility::Everyone,
+        }
+    }
+}
+
+/// What other players see at `GET /users/:id`. Sections hidden from the viewer
+/// are left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicProfile {
+    pub id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub joined_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clan: Option<ProfileClan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub games: Option<Vec<ProfileGame>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub showcase: Option<Vec<ShowcaseItem>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProfileClan {
+    pub id: String,
+    pub name: String,
+    pub tag: String,
+    pub rank: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProfileGame {
+    pub game_name: String,
+    pub linked_at: DateTime<Utc>,
+}
+
+/// An equipped item as shown on a profile
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ShowcaseItem {
+    pub id: String,
+    pub name: String,
+    pub custom_name: Option<String>,
+    pub item_type: String,
+    pub rarity: String,
+    pub icon_url: Option<String>,
+}
+
+/// Query parameters of `GET /users/search`. Needs `q`, `game`, or both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSearchQuery {
+    /// Username prefix or approximate spelling
+    pub q: Option<String>,
+    /// Only players who linked this game
+    pub game: Option<String>,
+    /// Exact in-game account ID within `game`
+    pub game_user_id: Option<String>,
+    pub page: Option<i64>,     // starts at 1
+    pub per_page: Option<i64>, // defaults to 20, at most 50
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserSummary {
+    pub id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSearchResponse {
+    pub results: Vec<UserSummary>,
+    pub page: i64,
+    pub per_page: i64,
+    pub has_more: bool,
+}
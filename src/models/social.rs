@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FriendRequest {
+    pub id: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendFriendRequest {
+    pub user_id: String,
+}
+
+/// A pending request as listed in the inbox; `user_id` is the other party
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FriendRequestInfo {
+    pub id: String,
+    pub user_id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FriendRequestInbox {
+    /// Requests waiting for the caller to accept or decline
+    pub incoming: Vec<FriendRequestInfo>,
+    /// Requests the caller has sent that are still pending
+    pub outgoing: Vec<FriendRequestInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Friend {
+    pub user_id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub friends_since: DateTime<Utc>,
+    /// Seen within the last few minutes
+    pub online: bool,
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockUserRequest {
+    pub user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BlockedUser {
+    pub user_id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub blocked_at: DateTime<Utc>,
+}
@@ -25,15 +25,16 @@ impl From<String> for TradeStatus {
     }
 }
 
-impl ToString for TradeStatus {
-    fn to_string(&self) -> String {
-        match self {
-            TradeStatus::Pending => "pending".to_string(),
-            TradeStatus::Accepted => "accepted".to_string(),
-            TradeStatus::Rejected => "rejected".to_string(),
-            TradeStatus::Cancelled => "cancelled".to_string(),
-            TradeStatus::Completed => "completed".to_string(),
-        }
+impl std::fmt::Display for TradeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            TradeStatus::Pending => "pending",
+            TradeStatus::Accepted => "accepted",
+            TradeStatus::Rejected => "rejected",
+            TradeStatus::Cancelled => "cancelled",
+            TradeStatus::Completed => "completed",
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -87,6 +88,73 @@ pub enum TradeAction {
     Counter,
 }
 
+/// Who may propose trades to a user
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TradePolicy {
+    Anyone,
+    Friends,
+    TrustedTraders,
+    Clanmates,
+    Nobody,
+}
+
+impl From<String> for TradePolicy {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "anyone" => TradePolicy::Anyone,
+            "friends" => TradePolicy::Friends,
+            "trusted_traders" => TradePolicy::TrustedTraders,
+            "clanmates" => TradePolicy::Clanmates,
+            _ => TradePolicy::Nobody,
+        }
+    }
+}
+
+impl std::fmt::Display for TradePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            TradePolicy::Anyone => "anyone",
+            TradePolicy::Friends => "friends",
+            TradePolicy::TrustedTraders => "trusted_traders",
+            TradePolicy::Clanmates => "clanmates",
+            TradePolicy::Nobody => "nobody",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+/// Row of `user_trade_settings`; also the body of `PUT /users/me/trade-settings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSettings {
+    pub inbound_policy: TradePolicy,
+    /// Accept and settle trades from trusted traders straight away when only they offer items
+    pub auto_accept_trusted_gifts: bool,
+}
+
+impl Default for TradeSettings {
+    /// Matches the column defaults of `user_trade_settings`
+    fn default() -> Self {
+        Self {
+            inbound_policy: TradePolicy::Anyone,
+            auto_accept_trusted_gifts: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TrustedTrader {
+    pub user_id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub trusted_since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustTraderRequest {
+    pub user_id: String,
+}
+
 impl Trade {
     pub fn new(initiator_id: String, recipient_id: String) -> Self {
         let now = Utc::now();
@@ -4,15 +4,20 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::{
+    AuctionBid, ClanMember, MarketplaceListing, Message, MfaChallenge, PrivacySettings, Trade,
+    TradeItem, TradeSettings, UserInventory, UserItemModifier,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: String,
     pub email: String,
+    pub email_verified_at: Option<DateTime<Utc>>,
     pub username: String,
     #[serde(skip_serializing)]
     pub password_hash: String,
     pub avatar_url: Option<String>,
-    pub discord_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login: Option<DateTime<Utc>>,
@@ -34,9 +39,17 @@ pub struct CreateUserRequest {
 pub struct UpdateUserRequest {
     #[validate(length(min = 3, max = 50))]
     pub username: Option<String>,
+    #[validate(url, length(max = 500))]
     pub avatar_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    #[validate(length(min = 8))]
+    pub new_password: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoginRequest {
     pub email: String,
@@ -44,24 +57,111 @@ pub struct LoginRequest {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LoginResponse {
-    pub user: User,
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    #[validate(length(min = 8))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated {
+        user: User,
+        #[serde(flatten)]
+        tokens: AuthTokens,
+    },
+    /// The password was correct but a second factor is still required
+    MfaRequired(MfaChallenge),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthTokens {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64, // access token lifetime in seconds
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct UserSession {
     pub id: String,
     pub user_id: String,
+    #[serde(skip_serializing)]
     pub token_hash: String,
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
     pub last_used: DateTime<Utc>,
     pub user_agent: Option<String>,
     pub ip_address: Option<String>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeSessionsResponse {
+    pub revoked: u64,
+}
+
+/// Row of the `login_attempts` audit log
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LoginAttempt {
+    pub id: i64,
+    pub email: String,
+    pub user_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+    pub outcome: String,
+    pub attempted_at: DateTime<Utc>,
 }
 
+/// Query parameters for searching the login audit log
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginAttemptFilter {
+    pub email: Option<String>,
+    pub user_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub limit: Option<i64>, // defaults to 100, at most 500
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserIdentity {
+    pub id: i32,
+    pub user_id: String,
+    pub provider: String,
+    pub provider_user_id: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub linked_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct UserGame {
     pub id: i32,
     pub user_id: String,
@@ -89,13 +189,14 @@ impl From<String> for ConnectionType {
     }
 }
 
-impl ToString for ConnectionType {
-    fn to_string(&self) -> String {
-        match self {
-            ConnectionType::Friend => "friend".to_string(),
-            ConnectionType::Trader => "trader".to_string(),
-            ConnectionType::Blocked => "blocked".to_string(),
-        }
+impl std::fmt::Display for ConnectionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ConnectionType::Friend => "friend",
+            ConnectionType::Trader => "trader",
+            ConnectionType::Blocked => "blocked",
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -108,16 +209,59 @@ pub struct UserConnection {
     pub created_at: DateTime<Utc>,
 }
 
+impl UserSession {
+    pub fn new(
+        user_id: String,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            token_hash,
+            expires_at,
+            created_at: now,
+            last_used: now,
+            user_agent,
+            ip_address,
+            revoked_at: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > Utc::now()
+    }
+
+    pub fn to_info(&self, current_session_id: &str) -> SessionInfo {
+        SessionInfo {
+            id: self.id.clone(),
+            created_at: self.created_at,
+            last_used: self.last_used,
+            expires_at: self.expires_at,
+            user_agent: self.user_agent.clone(),
+            ip_address: self.ip_address.clone(),
+            current: self.id == current_session_id,
+        }
+    }
+}
+
 impl User {
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
     pub fn new(email: String, username: String, password_hash: String) -> Self {
         let now = Utc::now();
         Self {
             id: Uuid::new_v4().to_string(),
             email,
+            email_verified_at: None,
             username,
             password_hash,
             avatar_url: None,
-            discord_id: None,
             created_at: now,
             updated_at: now,
             last_login: None,
@@ -125,3 +269,49 @@ impl User {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+    /// TOTP or recovery code, required when two-factor authentication is enabled
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDeletionResponse {
+    pub deleted_at: DateTime<Utc>,
+    /// Remaining data is permanently removed after this time
+    pub purge_after: DateTime<Utc>,
+}
+
+/// Everything stored about a user, returned by `GET /users/me/export`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountExport {
+    pub exported_at: DateTime<Utc>,
+    pub profile: User,
+    pub privacy: PrivacySettings,
+    pub trade_settings: TradeSettings,
+    pub identities: Vec<UserIdentity>,
+    pub games: Vec<UserGame>,
+    pub inventory: Vec<ExportedItem>,
+    pub trades: Vec<ExportedTrade>,
+    pub messages: Vec<Message>,
+    pub clan_memberships: Vec<ClanMember>,
+    pub listings: Vec<MarketplaceListing>,
+    pub bids: Vec<AuctionBid>,
+    pub login_attempts: Vec<LoginAttempt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedItem {
+    #[serde(flatten)]
+    pub item: UserInventory,
+    pub modifiers: Vec<UserItemModifier>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedTrade {
+    #[serde(flatten)]
+    pub trade: Trade,
+    pub items: Vec<TradeItem>,
+}
@@ -0,0 +1,435 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{MySql, MySqlPool, Transaction};
+
+use crate::{
+    models::{
+        AccountExport, ClanMember, ExportedItem, ExportedTrade, Trade, TradeItem, User,
+        UserInventory, UserItemModifier,
+    },
+    services::{games, profiles, trading},
+    utils::{self, errors::Result, AppError, PasswordHasher},
+};
+
+/// How often the purge job looks for accounts past their grace period
+const PURGE_INTERVAL_MINUTES: i64 = 60;
+
+/// Collect everything stored about a user
+pub async fn export(db: &MySqlPool, user_id: &str) -> Result<AccountExport> {
+    let profile: User = sqlx::query_as("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::not_found("User"))?;
+
+    let identities = sqlx::query_as("SELECT * FROM user_identities WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_all(db)
+        .await?;
+
+    let items: Vec<UserInventory> = sqlx::query_as(
+        "SELECT id, user_id, item_id, acquired_at, is_tradeable, is_equipped, custom_name
+         FROM user_inventory WHERE user_id = ? ORDER BY acquired_at",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let modifiers: Vec<UserItemModifier> = sqlx::query_as(
+        "SELECT m.* FROM user_item_modifiers m
+         JOIN user_inventory ui ON ui.id = m.user_item_id
+         WHERE ui.user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let inventory = items
+        .into_iter()
+        .map(|item| ExportedItem {
+            modifiers: modifiers
+                .iter()
+                .filter(|m| m.user_item_id == item.id)
+                .cloned()
+                .collect(),
+            item,
+        })
+        .collect();
+
+    let trades: Vec<Trade> = sqlx::query_as(
+        "SELECT * FROM trades WHERE initiator_id = ? OR recipient_id = ? ORDER BY created_at",
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let trade_items: Vec<TradeItem> = sqlx::query_as(
+        "SELECT ti.* FROM trade_items ti
+         JOIN trades t ON t.id = ti.trade_id
+         WHERE t.initiator_id = ? OR t.recipient_id = ?",
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let trades = trades
+        .into_iter()
+        .map(|trade| ExportedTrade {
+            items: trade_items
+                .iter()
+                .filter(|item| item.trade_id == trade.id)
+                .cloned()
+                .collect(),
+            trade,
+        })
+        .collect();
+
+    let messages = sqlx::query_as(
+        "SELECT * FROM messages WHERE sender_id = ? OR recipient_id = ? ORDER BY created_at",
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let clan_memberships = sqlx::query_as("SELECT * FROM clan_members WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_all(db)
+        .await?;
+
+    let listings = sqlx::query_as(
+        "SELECT * FROM marketplace_listings WHERE seller_id = ? OR buyer_id = ? ORDER BY created_at",
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let bids = sqlx::query_as("SELECT * FROM auction_bids WHERE bidder_id = ? ORDER BY created_at")
+        .bind(user_id)
+        .fetch_all(db)
+        .await?;
+
+    let login_attempts =
+        sqlx::query_as("SELECT * FROM login_attempts WHERE user_id = ? ORDER BY attempted_at DESC")
+            .bind(user_id)
+            .fetch_all(db)
+            .await?;
+
+    Ok(AccountExport {
+        exported_at: Utc::now(),
+        profile,
+        privacy: profiles::privacy_settings(db, user_id).await?,
+        trade_settings: trading::trade_settings(db, user_id).await?,
+        identities,
+        games: games::list_user_games(db, user_id).await?,
+        inventory,
+        trades,
+        messages,
+        clan_memberships,
+        listings,
+        bids,
+        login_attempts,
+    })
+}
+
+/// Close an account: wind down open trades, listings and clan roles, strip
+/// personal data and sign the user out everywhere. What remains (inventory,
+/// trade and message history) is removed by `purge_deleted_accounts` once the
+/// grace period is over.
+pub async fn delete_account(
+    db: &MySqlPool,
+    passwords: &PasswordHasher,
+    user_id: &str,
+) -> Result<DateTime<Utc>> {
+    let deleted_at = Utc::now();
+    let mut tx = db.begin().await?;
+
+    let user: Option<User> =
+        sqlx::query_as("SELECT * FROM users WHERE id = ? AND deleted_at IS NULL FOR UPDATE")
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let user = user.ok_or_else(|| AppError::not_found("User"))?;
+
+    sqlx::query(
+        "UPDATE trades SET status = 'cancelled'
+         WHERE (initiator_id = ? OR recipient_id = ?) AND status IN ('pending', 'accepted')",
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE marketplace_listings SET status = 'cancelled'
+         WHERE seller_id = ? AND status = 'active'",
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    // Withdraw bids on auctions that are still running
+    sqlx::query(
+        "DELETE b FROM auction_bids b
+         JOIN marketplace_listings l ON l.id = b.listing_id
+         WHERE b.bidder_id = ? AND l.status = 'active'",
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    leave_clans(&mut tx, user_id).await?;
+
+    // Anonymize the account; the row stays so history keeps its references
+    let anonymous = user_id.replace('-', "");
+    sqlx::query(
+        "UPDATE users SET email = ?, email_verified_at = NULL, username = ?, password_hash = ?,
+         avatar_url = NULL, last_login = NULL, is_active = false, deleted_at = ?
+         WHERE id = ?",
+    )
+    .bind(format!("deleted-{}@deleted.invalid", anonymous))
+    .bind(format!("deleted_{}", anonymous))
+    .bind(passwords.hash(&utils::generate_session_token())?)
+    .bind(deleted_at)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE login_attempts SET email = ?, ip_address = NULL, user_agent = NULL
+         WHERE user_id = ? OR email = ?",
+    )
+    .bind(format!("deleted-{}@deleted.invalid", anonymous))
+    .bind(user_id)
+    .bind(&user.email)
+    .execute(&mut *tx)
+    .await?;
+
+    for sql in [
+        "DELETE FROM user_sessions WHERE user_id = ?",
+        "DELETE FROM user_identities WHERE user_id = ?",
+        "DELETE FROM oauth_states WHERE user_id = ?",
+        "DELETE FROM user_tokens WHERE user_id = ?",
+        "DELETE FROM user_totp WHERE user_id = ?",
+        "DELETE FROM user_recovery_codes WHERE user_id = ?",
+        "DELETE FROM user_roles WHERE user_id = ?",
+        "DELETE c FROM game_link_codes c
+         JOIN user_games g ON g.game_name = c.game_name AND g.game_user_id = c.game_user_id
+         WHERE g.user_id = ?",
+        "DELETE FROM game_link_attempts WHERE user_id = ?",
+        "DELETE FROM user_games WHERE user_id = ?",
+        "DELETE FROM user_privacy_settings WHERE user_id = ?",
+        "DELETE FROM user_trade_settings WHERE user_id = ?",
+    ] {
+        sqlx::query(sql).bind(user_id).execute(&mut *tx).await?;
+    }
+
+    for sql in [
+        "DELETE FROM user_connections WHERE user_id = ? OR connected_user_id = ?",
+        "DELETE FROM friend_requests WHERE sender_id = ? OR recipient_id = ?",
+        "DELETE FROM clan_invites WHERE user_id = ? OR invited_by = ?",
+    ] {
+        sqlx::query(sql)
+            .bind(user_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    tracing::info!(user_id = %user_id, "Account deleted");
+
+    Ok(deleted_at)
+}
+
+/// Permanently remove accounts deleted more than `grace` ago, returning how many were purged
+pub async fn purge_deleted_accounts(db: &MySqlPool, grace: Duration) -> Result<u64> {
+    let due: Vec<(String,)> =
+        sqlx::query_as("SELECT id FROM users WHERE deleted_at IS NOT NULL AND deleted_at < ?")
+            .bind(Utc::now() - grace)
+            .fetch_all(db)
+            .await?;
+
+    let mut purged = 0;
+    for (user_id,) in due {
+        match purge_account(db, &user_id).await {
+            Ok(()) => purged += 1,
+            Err(e) => tracing::error!(user_id = %user_id, "Failed to purge deleted account: {}", e),
+        }
+    }
+
+    Ok(purged)
+}
+
+/// Run `purge_deleted_accounts` in the background every hour
+pub fn spawn_purge_job(db: MySqlPool, grace: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(
+            Duration::minutes(PURGE_INTERVAL_MINUTES)
+                .to_std()
+                .expect("purge interval is positive"),
+        );
+
+        loop {
+            interval.tick().await;
+
+            match purge_deleted_accounts(&db, grace).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} deleted accounts", purged),
+                Err(e) => tracing::error!("Deleted account purge failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Hand over leadership of the user's clans and drop their memberships. The
+/// successor is the longest-serving officer, or failing that the longest-serving
+/// member; clans left empty are deactivated.
+async fn leave_clans(tx: &mut Transaction<'_, MySql>, user_id: &str) -> Result<()> {
+    let led: Vec<(String,)> =
+        sqlx::query_as("SELECT clan_id FROM clan_members WHERE user_id = ? AND `rank` = 'Leader'")
+            .bind(user_id)
+            .fetch_all(&mut **tx)
+            .await?;
+
+    for (clan_id,) in led {
+        let successor: Option<ClanMember> = sqlx::query_as(
+            "SELECT * FROM clan_members WHERE clan_id = ? AND user_id != ?
+             ORDER BY `rank` = 'Officer' DESC, joined_at ASC
+             LIMIT 1",
+        )
+        .bind(&clan_id)
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        match successor {
+            Some(successor) => {
+                sqlx::query("UPDATE clan_members SET `rank` = 'Leader' WHERE id = ?")
+                    .bind(successor.id)
+                    .execute(&mut **tx)
+                    .await?;
+
+                tracing::info!(
+                    clan_id = %clan_id,
+                    new_leader = %successor.user_id,
+                    "Clan leadership transferred from deleted account"
+                );
+            }
+            None => {
+                sqlx::query("UPDATE clans SET is_active = false WHERE id = ?")
+                    .bind(&clan_id)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+        }
+    }
+
+    sqlx::query("DELETE FROM clan_members WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Delete an anonymized account and everything that still references it
+async fn purge_account(db: &MySqlPool, user_id: &str) -> Result<()> {
+    let mut tx = db.begin().await?;
+
+    sqlx::query("DELETE FROM messages WHERE sender_id = ? OR recipient_id = ?")
+        .bind(user_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM auction_bids WHERE bidder_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // Trade items cascade with their trades
+    sqlx::query("DELETE FROM trades WHERE initiator_id = ? OR recipient_id = ?")
+        .bind(user_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // Listings by the user or for items they now own would block deleting the inventory
+    sqlx::query(
+        "DELETE FROM marketplace_listings
+         WHERE seller_id = ? OR user_item_id IN (SELECT id FROM user_inventory WHERE user_id = ?)",
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE marketplace_listings SET buyer_id = NULL WHERE buyer_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    purge_clan_references(&mut tx, user_id).await?;
+
+    // Sessions, identities, inventory and other owned rows cascade
+    sqlx::query("DELETE FROM users WHERE id = ? AND deleted_at IS NOT NULL")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(user_id = %user_id, "Deleted account purged");
+
+    Ok(())
+}
+
+/// Clans the user founded are credited to their current leader; ones without
+/// members are removed. Items the user deposited stay with the clan.
+async fn purge_clan_references(tx: &mut Transaction<'_, MySql>, user_id: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE clans c
+         JOIN clan_members m ON m.clan_id = c.id AND m.`rank` = 'Leader'
+         SET c.created_by = m.user_id
+         WHERE c.created_by = ?",
+    )
+    .bind(user_id)
+    .execute(&mut **tx)
+    .await?;
+
+    let orphaned: Vec<(String,)> = sqlx::query_as("SELECT id FROM clans WHERE created_by = ?")
+        .bind(user_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    for (clan_id,) in orphaned {
+        sqlx::query("UPDATE clans SET federation_id = NULL WHERE federation_id = ?")
+            .bind(&clan_id)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query("UPDATE marketplace_listings SET clan_id = NULL WHERE clan_id = ?")
+            .bind(&clan_id)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query("DELETE FROM clans WHERE id = ?")
+            .bind(&clan_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    sqlx::query(
+        "UPDATE clan_inventory ci
+         JOIN clans c ON c.id = ci.clan_id
+         SET ci.deposited_by = c.created_by
+         WHERE ci.deposited_by = ?",
+    )
+    .bind(user_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
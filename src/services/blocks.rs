@@ -0,0 +1,196 @@
+use sqlx::MySqlPool;
+
+use crate::{
+    models::BlockedUser,
+    utils::{errors::Result, AppError},
+};
+
+/// Something one user does to another that a block rules out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interaction {
+    Message,
+    Trade,
+    Bid,
+    FriendRequest,
+    ClanInvite,
+}
+
+impl Interaction {
+    fn describe(&self) -> &'static str {
+        match self {
+            Interaction::Message => "message",
+            Interaction::Trade => "trade with",
+            Interaction::Bid => "bid on listings from",
+            Interaction::FriendRequest => "send a friend request to",
+            Interaction::ClanInvite => "invite",
+        }
+    }
+}
+
+/// Whether either user has blocked the other
+pub async fn is_blocked_between(db: &MySqlPool, user_id: &str, other_id: &str) -> Result<bool> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM user_connections
+         WHERE connection_type = 'blocked'
+           AND ((user_id = ? AND connected_user_id = ?) OR (user_id = ? AND connected_user_id = ?))",
+    )
+    .bind(user_id)
+    .bind(other_id)
+    .bind(other_id)
+    .bind(user_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(count > 0)
+}
+
+/// Condition that no block exists either way between the user in `user_column`
+/// and the viewer, who is bound twice. Lists filter with this the same way
+/// `ensure_can_interact` guards actions.
+pub(crate) fn not_blocked_sql(user_column: &str) -> String {
+    format!(
+        "NOT EXISTS (SELECT 1 FROM user_connections bl
+                     WHERE bl.connection_type = 'blocked'
+                       AND ((bl.user_id = {col} AND bl.connected_user_id = ?)
+                         OR (bl.user_id = ? AND bl.connected_user_id = {col})))",
+        col = user_column
+    )
+}
+
+/// The policy every handler applies before `actor_id` does something to
+/// `target_id`. Blocks work both ways, and the error doesn't say who blocked whom.
+pub async fn ensure_can_interact(
+    db: &MySqlPool,
+    actor_id: &str,
+    target_id: &str,
+    interaction: Interaction,
+) -> Result<()> {
+    if is_blocked_between(db, actor_id, target_id).await? {
+        return Err(AppError::Forbidden(format!(
+            "You can't {} this user",
+            interaction.describe()
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn list_blocked(db: &MySqlPool, user_id: &str) -> Result<Vec<BlockedUser>> {
+    Ok(sqlx::query_as(
+        "SELECT u.id AS user_id, u.username, u.avatar_url, c.created_at AS blocked_at
+         FROM user_connections c
+         JOIN users u ON u.id = c.connected_user_id
+         WHERE c.user_id = ? AND c.connection_type = 'blocked'
+         ORDER BY c.created_at DESC",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?)
+}
+
+/// Block a user, ending any friendship and cancelling pending friend requests,
+/// clan invites, open trades and bids on each other's auctions between the two.
+/// Blocking someone already blocked is a no-op.
+pub async fn block_user(db: &MySqlPool, user_id: &str, target_id: &str) -> Result<()> {
+    if user_id == target_id {
+        return Err(AppError::bad_request("You can't block yourself"));
+    }
+
+    let target: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM users WHERE id = ? AND deleted_at IS NULL")
+            .bind(target_id)
+            .fetch_optional(db)
+            .await?;
+    if target.is_none() {
+        return Err(AppError::not_found("User"));
+    }
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        "INSERT IGNORE INTO user_connections (user_id, connected_user_id, connection_type)
+         VALUES (?, ?, 'blocked')",
+    )
+    .bind(user_id)
+    .bind(target_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "DELETE FROM user_connections
+         WHERE connection_type != 'blocked'
+           AND ((user_id = ? AND connected_user_id = ?) OR (user_id = ? AND connected_user_id = ?))",
+    )
+    .bind(user_id)
+    .bind(target_id)
+    .bind(target_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "DELETE FROM friend_requests
+         WHERE (sender_id = ? AND recipient_id = ?) OR (sender_id = ? AND recipient_id = ?)",
+    )
+    .bind(user_id)
+    .bind(target_id)
+    .bind(target_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "DELETE FROM clan_invites
+         WHERE (invited_by = ? AND user_id = ?) OR (invited_by = ? AND user_id = ?)",
+    )
+    .bind(user_id)
+    .bind(target_id)
+    .bind(target_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    // Withdraw bids either side has running on the other's auctions
+    sqlx::query(
+        "DELETE b FROM auction_bids b
+         JOIN marketplace_listings l ON l.id = b.listing_id
+         WHERE l.status = 'active'
+           AND ((b.bidder_id = ? AND l.seller_id = ?) OR (b.bidder_id = ? AND l.seller_id = ?))",
+    )
+    .bind(user_id)
+    .bind(target_id)
+    .bind(target_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE trades SET status = 'cancelled'
+         WHERE status IN ('pending', 'accepted')
+           AND ((initiator_id = ? AND recipient_id = ?) OR (initiator_id = ? AND recipient_id = ?))",
+    )
+    .bind(user_id)
+    .bind(target_id)
+    .bind(target_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Returns false if the user wasn't blocked
+pub async fn unblock_user(db: &MySqlPool, user_id: &str, target_id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "DELETE FROM user_connections
+         WHERE user_id = ? AND connected_user_id = ? AND connection_type = 'blocked'",
+    )
+    .bind(user_id)
+    .bind(target_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
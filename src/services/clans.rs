@@ -0,0 +1,214 @@
+use chrono::Utc;
+use sqlx::MySqlPool;
+use uuid::Uuid;
+
+use crate::{
+    models::{ClanInvite, ClanMember, ClanRank},
+    services::blocks::{self, Interaction},
+    utils::{errors::Result, AppError},
+};
+
+/// Invite a player to an active clan. Leaders and officers can invite; the
+/// player must not already be a member, and a block either way rules it out.
+pub async fn invite_member(
+    db: &MySqlPool,
+    clan_id: &str,
+    inviter_id: &str,
+    user_id: &str,
+) -> Result<ClanInvite> {
+    let inviter_rank: Option<(String,)> = sqlx::query_as(
+        "SELECT m.`rank` FROM clan_members m
+         JOIN clans c ON c.id = m.clan_id AND c.is_active = true
+         WHERE m.clan_id = ? AND m.user_id = ?",
+    )
+    .bind(clan_id)
+    .bind(inviter_id)
+    .fetch_optional(db)
+    .await?;
+    let Some((rank,)) = inviter_rank else {
+        return Err(AppError::not_found("Clan"));
+    };
+    if ClanRank::from(rank) == ClanRank::Member {
+        return Err(AppError::forbidden(
+            "Only leaders and officers can invite players",
+        ));
+    }
+
+    let invitee: Option<(String,)> = sqlx::query_as(
+        "SELECT id FROM users WHERE id = ? AND is_active = true AND deleted_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    if invitee.is_none() {
+        return Err(AppError::not_found("User"));
+    }
+
+    blocks::ensure_can_interact(db, inviter_id, user_id, Interaction::ClanInvite).await?;
+
+    let (members,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM clan_members WHERE clan_id = ? AND user_id = ?")
+            .bind(clan_id)
+            .bind(user_id)
+            .fetch_one(db)
+            .await?;
+    if members > 0 {
+        return Err(AppError::Conflict(
+            "This user is already a member of the clan".to_string(),
+        ));
+    }
+
+    let invite = ClanInvite {
+        id: Uuid::new_v4().to_string(),
+        clan_id: clan_id.to_string(),
+        user_id: user_id.to_string(),
+        invited_by: inviter_id.to_string(),
+        created_at: Utc::now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO clan_invites (id, clan_id, user_id, invited_by, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&invite.id)
+    .bind(&invite.clan_id)
+    .bind(&invite.user_id)
+    .bind(&invite.invited_by)
+    .bind(invite.created_at)
+    .execute(db)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::Conflict("This user has already been invited".to_string())
+        }
+        e => AppError::Database(e),
+    })?;
+
+    Ok(invite)
+}
+
+/// Join the clan an invite addressed to the user is for, if it still has room
+pub async fn accept_invite(db: &MySqlPool, user_id: &str, invite_id: &str) -> Result<ClanMember> {
+    let mut tx = db.begin().await?;
+
+    let invite: ClanInvite =
+        sqlx::query_as("SELECT * FROM clan_invites WHERE id = ? AND user_id = ? FOR UPDATE")
+            .bind(invite_id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::not_found("Clan invite"))?;
+
+    let clan: Option<(i32,)> = sqlx::query_as(
+        "SELECT max_members FROM clans WHERE id = ? AND is_active = true FOR UPDATE",
+    )
+    .bind(&invite.clan_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((max_members,)) = clan else {
+        return Err(AppError::not_found("Clan"));
+    };
+
+    let (members,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM clan_members WHERE clan_id = ?")
+        .bind(&invite.clan_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if members >= i64::from(max_members) {
+        return Err(AppError::Conflict("This clan is full".to_string()));
+    }
+
+    sqlx::query("DELETE FROM clan_invites WHERE id = ?")
+        .bind(&invite.id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("INSERT IGNORE INTO clan_members (clan_id, user_id, `rank`) VALUES (?, ?, ?)")
+        .bind(&invite.clan_id)
+        .bind(user_id)
+        .bind(ClanRank::Member.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+    let member: ClanMember =
+        sqlx::query_as("SELECT * FROM clan_members WHERE clan_id = ? AND user_id = ?")
+            .bind(&invite.clan_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    tx.commit().await?;
+
+    Ok(member)
+}
+
+/// Turn down an invite addressed to the user
+pub async fn decline_invite(db: &MySqlPool, user_id: &str, invite_id: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM clan_invites WHERE id = ? AND user_id = ?")
+        .bind(invite_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("Clan invite"));
+    }
+
+    Ok(())
+}
+
+/// Make another member the leader of an active clan. Only the current leader can
+/// do this; they stay in the clan as an officer.
+pub async fn transfer_leadership(
+    db: &MySqlPool,
+    clan_id: &str,
+    leader_id: &str,
+    new_leader_id: &str,
+) -> Result<ClanMember> {
+    if leader_id == new_leader_id {
+        return Err(AppError::bad_request("You already lead this clan"));
+    }
+
+    let mut tx = db.begin().await?;
+
+    let leader: ClanMember = sqlx::query_as(
+        "SELECT m.* FROM clan_members m
+         JOIN clans c ON c.id = m.clan_id AND c.is_active = true
+         WHERE m.clan_id = ? AND m.user_id = ? FOR UPDATE",
+    )
+    .bind(clan_id)
+    .bind(leader_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::not_found("Clan"))?;
+
+    if ClanRank::from(leader.rank) != ClanRank::Leader {
+        return Err(AppError::forbidden(
+            "Only the clan leader can transfer leadership",
+        ));
+    }
+
+    let mut successor: ClanMember =
+        sqlx::query_as("SELECT * FROM clan_members WHERE clan_id = ? AND user_id = ? FOR UPDATE")
+            .bind(clan_id)
+            .bind(new_leader_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::not_found("Clan member"))?;
+
+    sqlx::query("UPDATE clan_members SET `rank` = ? WHERE id = ?")
+        .bind(ClanRank::Officer.to_string())
+        .bind(leader.id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("UPDATE clan_members SET `rank` = ? WHERE id = ?")
+        .bind(ClanRank::Leader.to_string())
+        .bind(successor.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    successor.rank = ClanRank::Leader.to_string();
+    Ok(successor)
+}
@@ -0,0 +1,241 @@
+use sqlx::MySqlPool;
+use uuid::Uuid;
+
+use crate::{
+    models::{CreateGameClientRequest, GameClient, ANY_GAME},
+    utils::{self, errors::Result, AppError},
+};
+
+/// Scopes a game client can be given. They share names with the permissions
+/// checked by `RequirePermission`, so one route guard serves users and servers.
+pub const GAME_CLIENT_SCOPES: &[&str] = &[
+    "items:grant",
+    "inventory:read",
+    "games:link",
+    "games:sync",
+    "trades:settle",
+];
+
+const API_KEY_PREFIX: &str = "bk_";
+/// Characters of the key kept in clear so admins can tell keys apart
+const KEY_PREFIX_LENGTH: usize = 11;
+
+pub struct NewGameClient {
+    pub client: GameClient,
+    pub api_key: String,
+    pub signing_secret: String,
+}
+
+/// Register a game client, returning it with its plaintext API key and signing secret
+pub async fn create_client(
+    db: &MySqlPool,
+    request: &CreateGameClientRequest,
+    created_by: &str,
+) -> Result<NewGameClient> {
+    if let Some(scope) = request
+        .scopes
+        .iter()
+        .find(|scope| !GAME_CLIENT_SCOPES.contains(&scope.as_str()))
+    {
+        return Err(AppError::Validation(format!(
+            "Unknown scope '{}'; expected one of: {}",
+            scope,
+            GAME_CLIENT_SCOPES.join(", ")
+        )));
+    }
+
+    if request
+        .allowed_games
+        .iter()
+        .any(|game| game.trim().is_empty() || game.chars().any(char::is_whitespace))
+    {
+        return Err(AppError::Validation(
+            "Game names can't be empty or contain spaces".to_string(),
+        ));
+    }
+
+    for game in request
+        .allowed_games
+        .iter()
+        .filter(|game| *game != ANY_GAME)
+    {
+        let known: Option<(i32,)> = sqlx::query_as("SELECT id FROM games WHERE name = ?")
+            .bind(game)
+            .fetch_optional(db)
+            .await?;
+        if known.is_none() {
+            return Err(AppError::Validation(format!("Unknown game '{}'", game)));
+        }
+    }
+
+    let api_key = format!("{}{}", API_KEY_PREFIX, utils::generate_session_token());
+    let signing_secret = utils::generate_session_token();
+    let id = Uuid::new_v4().to_string();
+
+    sqlx::query(
+        "INSERT INTO game_clients
+         (id, name, key_prefix, key_hash, signing_secret, scopes, allowed_games, created_by)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(&request.name)
+    .bind(&api_key[..KEY_PREFIX_LENGTH])
+    .bind(utils::hash_token(&api_key))
+    .bind(&signing_secret)
+    .bind(request.scopes.join(" "))
+    .bind(request.allowed_games.join(" "))
+    .bind(created_by)
+    .execute(db)
+    .await?;
+
+    let client = find_client(db, &id).await?;
+
+    tracing::info!(client_id = %id, name = %client.name, created_by = %created_by, "Game client created");
+
+    Ok(NewGameClient {
+        client,
+        api_key,
+        signing_secret,
+    })
+}
+
+pub async fn list_clients(db: &MySqlPool) -> Result<Vec<GameClient>> {
+    let clients = sqlx::query_as("SELECT * FROM game_clients ORDER BY created_at DESC")
+        .fetch_all(db)
+        .await?;
+
+    Ok(clients)
+}
+
+/// Revoke a client's key; requests using it are rejected immediately
+pub async fn revoke_client(db: &MySqlPool, id: &str) -> Result<GameClient> {
+    let result = sqlx::query(
+        "UPDATE game_clients SET revoked_at = NOW() WHERE id = ? AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    let client = find_client(db, id).await?;
+
+    if result.rows_affected() > 0 {
+        tracing::info!(client_id = %id, name = %client.name, "Game client revoked");
+    }
+
+    Ok(client)
+}
+
+/// Replace a client's request signing secret, returning the new one.
+/// Requests signed with the old secret are rejected from now on.
+pub async fn rotate_signing_secret(db: &MySqlPool, id: &str) -> Result<String> {
+    let signing_secret = utils::generate_session_token();
+
+    let result = sqlx::query(
+        "UPDATE game_clients SET signing_secret = ? WHERE id = ? AND revoked_at IS NULL",
+    )
+    .bind(&signing_secret)
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("Game client"));
+    }
+
+    tracing::info!(client_id = %id, "Game client signing secret rotated");
+
+    Ok(signing_secret)
+}
+
+/// Look up an active client by ID for signature verification
+pub async fn find_active_client(db: &MySqlPool, id: &str) -> Result<GameClient> {
+    let client: Option<GameClient> =
+        sqlx::query_as("SELECT * FROM game_clients WHERE id = ? AND revoked_at IS NULL")
+            .bind(id)
+            .fetch_optional(db)
+            .await?;
+
+    let client = client.ok_or_else(|| AppError::Authentication("Unknown key ID".to_string()))?;
+
+    touch_last_used(db, &client.id).await?;
+
+    Ok(client)
+}
+
+/// Resolve an `X-Bridge-Key` header value to an active client
+pub async fn authenticate(db: &MySqlPool, api_key: &str) -> Result<GameClient> {
+    let client: Option<GameClient> =
+        sqlx::query_as("SELECT * FROM game_clients WHERE key_hash = ? AND revoked_at IS NULL")
+            .bind(utils::hash_token(api_key))
+            .fetch_optional(db)
+            .await?;
+
+    let client = client.ok_or_else(|| AppError::Authentication("Invalid API key".to_string()))?;
+
+    touch_last_used(db, &client.id).await?;
+
+    Ok(client)
+}
+
+/// Whether a client may act for players of `game_name`
+pub fn allows_game(allowed_games: &[String], game_name: &str) -> bool {
+    allowed_games
+        .iter()
+        .any(|game| game == ANY_GAME || game == game_name)
+}
+
+/// Whether a client may handle an item from `game_name`; Bridge-wide items
+/// (no game) need a client allowed any game
+pub fn allows_item_game(allowed_games: &[String], game_name: Option<&str>) -> bool {
+    match game_name {
+        Some(game_name) => allows_game(allowed_games, game_name),
+        None => allowed_games.iter().any(|game| game == ANY_GAME),
+    }
+}
+
+/// Whether a client may act for `user_id`: the player must have linked one of
+/// the client's allowed games, unless the client is allowed any game
+pub async fn can_act_for(db: &MySqlPool, allowed_games: &[String], user_id: &str) -> Result<bool> {
+    if allowed_games.iter().any(|game| game == ANY_GAME) {
+        return Ok(true);
+    }
+    if allowed_games.is_empty() {
+        return Ok(false);
+    }
+
+    let placeholders = vec!["?"; allowed_games.len()].join(", ");
+    let sql = format!(
+        "SELECT COUNT(*) FROM user_games WHERE user_id = ? AND game_name IN ({})",
+        placeholders
+    );
+
+    let mut query = sqlx::query_as::<_, (i64,)>(&sql).bind(user_id);
+    for game in allowed_games {
+        query = query.bind(game);
+    }
+
+    let (linked,) = query.fetch_one(db).await?;
+
+    Ok(linked > 0)
+}
+
+/// Coarse usage tracking without a write on every request
+async fn touch_last_used(db: &MySqlPool, id: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE game_clients SET last_used_at = NOW()
+         WHERE id = ? AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL 1 MINUTE)",
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+async fn find_client(db: &MySqlPool, id: &str) -> Result<GameClient> {
+    sqlx::query_as("SELECT * FROM game_clients WHERE id = ?")
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::not_found("Game client"))
+}
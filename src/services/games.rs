@@ -0,0 +1,354 @@
+use chrono::{Duration, Utc};
+use rand::Rng;
+use sqlx::MySqlPool;
+use uuid::Uuid;
+
+use crate::{
+    config::LoginThrottleConfig,
+    models::{CreateGameRequest, Game, LinkCodeResponse, UserGame, ANY_GAME},
+    services::login_attempts::{self, AttemptLog},
+    utils::{self, errors::Result, AppError},
+};
+
+/// Unambiguous characters only (no 0/O, 1/I/L), upper case to read clearly in game
+const LINK_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+/// Characters per code, shown in groups of `LINK_CODE_GROUP`
+const LINK_CODE_LENGTH: usize = 12;
+const LINK_CODE_GROUP: usize = 4;
+
+pub async fn list_games(db: &MySqlPool) -> Result<Vec<Game>> {
+    Ok(
+        sqlx::query_as("SELECT * FROM games WHERE is_active = true ORDER BY display_name")
+            .fetch_all(db)
+            .await?,
+    )
+}
+
+pub async fn find_active_game(db: &MySqlPool, name: &str) -> Result<Game> {
+    sqlx::query_as("SELECT * FROM games WHERE name = ? AND is_active = true")
+        .bind(name)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::not_found("Game"))
+}
+
+/// Add a game to the catalog. Names are identifiers, so they can't contain spaces.
+pub async fn create_game(db: &MySqlPool, request: &CreateGameRequest) -> Result<Game> {
+    if request.name == ANY_GAME || request.name.chars().any(char::is_whitespace) {
+        return Err(AppError::Validation(
+            "Game names can't contain spaces or be '*'".to_string(),
+        ));
+    }
+
+    let result = sqlx::query("INSERT INTO games (name, display_name) VALUES (?, ?)")
+        .bind(&request.name)
+        .bind(&request.display_name)
+        .execute(db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                AppError::Conflict("A game with this name already exists".to_string())
+            }
+            e => AppError::Database(e),
+        })?;
+
+    let game = sqlx::query_as("SELECT * FROM games WHERE id = ?")
+        .bind(result.last_insert_id() as i32)
+        .fetch_one(db)
+        .await?;
+
+    tracing::info!(game = %request.name, "Game added to catalog");
+
+    Ok(game)
+}
+
+/// Issue a code for a player to link `game_user_id` to their Bridge account.
+/// Earlier unused codes for the same game account stop working, and expired
+/// codes are cleared out.
+pub async fn issue_link_code(
+    db: &MySqlPool,
+    client_id: &str,
+    game_name: &str,
+    game_user_id: &str,
+    ttl: Duration,
+) -> Result<LinkCodeResponse> {
+    find_active_game(db, game_name).await?;
+
+    let code = generate_link_code();
+    let expires_at = Utc::now() + ttl;
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM game_link_codes
+         WHERE (game_name = ? AND game_user_id = ? AND used_at IS NULL) OR expires_at < NOW()",
+    )
+    .bind(game_name)
+    .bind(game_user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO game_link_codes (id, code_hash, game_name, game_user_id, client_id, expires_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(utils::hash_token(&normalize_link_code(&code)))
+    .bind(game_name)
+    .bind(game_user_id)
+    .bind(client_id)
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(LinkCodeResponse {
+        code,
+        game_name: game_name.to_string(),
+        game_user_id: game_user_id.to_string(),
+        expires_at,
+    })
+}
+
+const LINK_ATTEMPTS: AttemptLog = AttemptLog {
+    table: "game_link_attempts",
+    account_column: "user_id",
+    counted_failures: "success = false",
+};
+
+/// Refuse link attempts while the user or their IP is backing off after
+/// wrong codes, counted the same way as the login backoff
+async fn check_link_throttle(
+    db: &MySqlPool,
+    config: &LoginThrottleConfig,
+    user_id: &str,
+    ip_address: Option<&str>,
+) -> Result<()> {
+    match login_attempts::backoff_remaining(db, config, &LINK_ATTEMPTS, user_id, ip_address).await?
+    {
+        Some(remaining) => Err(AppError::TooManyRequests {
+            message: "Too many invalid link codes, try again later".to_string(),
+            retry_after: remaining.num_seconds().max(1) as u64,
+        }),
+        None => Ok(()),
+    }
+}
+
+async fn record_link_attempt(
+    db: &MySqlPool,
+    user_id: &str,
+    ip_address: Option<&str>,
+    success: bool,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO game_link_attempts (user_id, ip_address, success, attempted_at)
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(user_id)
+    .bind(ip_address)
+    .bind(success)
+    .bind(Utc::now())
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Link the game account a code was issued for to the user, consuming the code.
+/// A game account belongs to one Bridge account, and a user links one account per game.
+/// Wrong codes count towards a per-user and per-IP backoff.
+pub async fn link_game(
+    db: &MySqlPool,
+    throttle: &LoginThrottleConfig,
+    user_id: &str,
+    ip_address: Option<&str>,
+    code: &str,
+) -> Result<UserGame> {
+    check_link_throttle(db, throttle, user_id, ip_address).await?;
+
+    let mut tx = db.begin().await?;
+
+    let link: Option<(String, String, String)> = sqlx::query_as(
+        "SELECT id, game_name, game_user_id FROM game_link_codes
+         WHERE code_hash = ? AND used_at IS NULL AND expires_at > NOW()
+         FOR UPDATE",
+    )
+    .bind(utils::hash_token(&normalize_link_code(code)))
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((code_id, game_name, game_user_id)) = link else {
+        tx.rollback().await?;
+        record_link_attempt(db, user_id, ip_address, false).await?;
+        return Err(AppError::bad_request("Invalid or expired link code"));
+    };
+
+    let owner: Option<(String,)> =
+        sqlx::query_as("SELECT user_id FROM user_games WHERE game_name = ? AND game_user_id = ?")
+            .bind(&game_name)
+            .bind(&game_user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+    match owner {
+        Some((owner_id,)) if owner_id == user_id => {
+            return Err(AppError::Conflict(
+                "This game account is already linked to your account".to_string(),
+            ));
+        }
+        Some(_) => {
+            return Err(AppError::Conflict(
+                "This game account is linked to another Bridge account".to_string(),
+            ));
+        }
+        None => {}
+    }
+
+    sqlx::query("UPDATE game_link_codes SET used_at = NOW() WHERE id = ?")
+        .bind(&code_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("INSERT INTO user_games (user_id, game_name, game_user_id) VALUES (?, ?, ?)")
+        .bind(user_id)
+        .bind(&game_name)
+        .bind(&game_user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                "You already linked an account for this game; unlink it first".to_string(),
+            ),
+            e => AppError::Database(e),
+        })?;
+
+    let game: UserGame =
+        sqlx::query_as("SELECT * FROM user_games WHERE user_id = ? AND game_name = ?")
+            .bind(user_id)
+            .bind(&game_name)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    tx.commit().await?;
+
+    record_link_attempt(db, user_id, ip_address, true).await?;
+    tracing::info!(user_id = %user_id, game = %game_name, "Game account linked");
+
+    Ok(game)
+}
+
+pub async fn list_user_games(db: &MySqlPool, user_id: &str) -> Result<Vec<UserGame>> {
+    Ok(
+        sqlx::query_as("SELECT * FROM user_games WHERE user_id = ? ORDER BY linked_at")
+            .bind(user_id)
+            .fetch_all(db)
+            .await?,
+    )
+}
+
+/// Returns false if the user hadn't linked the game
+pub async fn unlink_game(db: &MySqlPool, user_id: &str, game_name: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM user_games WHERE user_id = ? AND game_name = ?")
+        .bind(user_id)
+        .bind(game_name)
+        .execute(db)
+        .await?;
+
+    if result.rows_affected() > 0 {
+        tracing::info!(user_id = %user_id, game = %game_name, "Game account unlinked");
+    }
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record that a game server has synced a player, returning the Bridge account
+/// their game account is linked to
+pub async fn sync_game_account(
+    db: &MySqlPool,
+    game_name: &str,
+    game_user_id: &str,
+) -> Result<UserGame> {
+    sqlx::query(
+        "UPDATE user_games SET last_synced = NOW() WHERE game_name = ? AND game_user_id = ?",
+    )
+    .bind(game_name)
+    .bind(game_user_id)
+    .execute(db)
+    .await?;
+
+    sqlx::query_as(
+        "SELECT g.* FROM user_games g
+         JOIN users u ON u.id = g.user_id
+         WHERE g.game_name = ? AND g.game_user_id = ?
+           AND u.is_active = true AND u.deleted_at IS NULL",
+    )
+    .bind(game_name)
+    .bind(game_user_id)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| AppError::not_found("Linked game account"))
+}
+
+/// Twelve characters split as `XXXX-XXXX-XXXX`
+fn generate_link_code() -> String {
+    let mut rng = rand::thread_rng();
+    let chars: Vec<u8> = (0..LINK_CODE_LENGTH)
+        .map(|_| LINK_CODE_ALPHABET[rng.gen_range(0..LINK_CODE_ALPHABET.len())])
+        .collect();
+
+    chars
+        .chunks(LINK_CODE_GROUP)
+        .map(|group| String::from_utf8_lossy(group).into_owned())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn normalize_link_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_codes_are_grouped() {
+        let code = generate_link_code();
+        let groups: Vec<&str> = code.split('-').collect();
+
+        assert_eq!(groups.len(), LINK_CODE_LENGTH / LINK_CODE_GROUP);
+        assert!(groups.iter().all(|group| group.len() == LINK_CODE_GROUP));
+        assert!(code
+            .bytes()
+            .filter(|c| *c != b'-')
+            .all(|c| LINK_CODE_ALPHABET.contains(&c)));
+    }
+
+    #[test]
+    fn generated_codes_normalize_without_separators() {
+        let code = generate_link_code();
+
+        assert_eq!(normalize_link_code(&code), code.replace('-', ""));
+        assert_eq!(normalize_link_code(&code).len(), LINK_CODE_LENGTH);
+    }
+
+    #[test]
+    fn normalization_ignores_case_spaces_and_dashes() {
+        let expected = "ABCD2345WXYZ";
+
+        assert_eq!(normalize_link_code("ABCD-2345-WXYZ"), expected);
+        assert_eq!(normalize_link_code("abcd-2345-wxyz"), expected);
+        assert_eq!(normalize_link_code(" abcd 2345 wxyz "), expected);
+        assert_eq!(normalize_link_code("ABCD2345WXYZ"), expected);
+    }
+
+    #[test]
+    fn normalization_drops_other_punctuation() {
+        assert_eq!(normalize_link_code("ab.cd_23/45"), "ABCD2345");
+        assert_eq!(normalize_link_code("--"), "");
+    }
+}
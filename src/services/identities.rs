@@ -0,0 +1,213 @@
+use sqlx::MySqlPool;
+
+use crate::{
+    auth::ExternalIdentity,
+    models::{User, UserIdentity},
+    utils::{self, errors::Result, AppError, PasswordHasher},
+};
+
+/// The user an external identity is linked to, if any
+pub async fn find_user(
+    db: &MySqlPool,
+    provider: &str,
+    identity: &ExternalIdentity,
+) -> Result<Option<User>> {
+    let user = sqlx::query_as(
+        "SELECT u.* FROM users u
+         JOIN user_identities i ON i.user_id = u.id
+         WHERE i.provider = ? AND i.provider_user_id = ?",
+    )
+    .bind(provider)
+    .bind(&identity.provider_user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(user)
+}
+
+pub async fn record_login(
+    db: &MySqlPool,
+    provider: &str,
+    identity: &ExternalIdentity,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE user_identities SET last_login_at = NOW(), username = ?, email = ?
+         WHERE provider = ? AND provider_user_id = ?",
+    )
+    .bind(&identity.username)
+    .bind(&identity.email)
+    .bind(provider)
+    .bind(&identity.provider_user_id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Attach an external identity to an existing user
+pub async fn link(
+    db: &MySqlPool,
+    user_id: &str,
+    provider: &str,
+    identity: &ExternalIdentity,
+) -> Result<UserIdentity> {
+    let existing: Option<UserIdentity> =
+        sqlx::query_as("SELECT * FROM user_identities WHERE user_id = ? AND provider = ?")
+            .bind(user_id)
+            .bind(provider)
+            .fetch_optional(db)
+            .await?;
+
+    match existing {
+        Some(linked) if linked.provider_user_id == identity.provider_user_id => return Ok(linked),
+        Some(_) => {
+            return Err(AppError::Conflict(format!(
+                "Account is already linked to a different {} account",
+                provider
+            )))
+        }
+        None => {}
+    }
+
+    sqlx::query(
+        "INSERT INTO user_identities (user_id, provider, provider_user_id, username, email)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(user_id)
+    .bind(provider)
+    .bind(&identity.provider_user_id)
+    .bind(&identity.username)
+    .bind(&identity.email)
+    .execute(db)
+    .await
+    .map_err(|e| {
+        if e.to_string().contains("Duplicate entry") {
+            AppError::Conflict(format!(
+                "This {} account is already linked to another user",
+                provider
+            ))
+        } else {
+            AppError::Database(e)
+        }
+    })?;
+
+    let linked = sqlx::query_as("SELECT * FROM user_identities WHERE user_id = ? AND provider = ?")
+        .bind(user_id)
+        .bind(provider)
+        .fetch_one(db)
+        .await?;
+
+    Ok(linked)
+}
+
+/// Create a new account for an external identity that isn't linked to anyone yet
+pub async fn register(
+    db: &MySqlPool,
+    passwords: &PasswordHasher,
+    provider: &str,
+    identity: &ExternalIdentity,
+) -> Result<User> {
+    let email = match (&identity.email, identity.email_verified) {
+        (Some(email), true) => email.clone(),
+        _ => {
+            return Err(AppError::BadRequest(format!(
+                "The {} account has no verified email address",
+                provider
+            )))
+        }
+    };
+
+    // Never attach an external login to an existing email/password account without
+    // the owner proving they control it by linking while signed in
+    let email_taken: Option<(String,)> = sqlx::query_as("SELECT id FROM users WHERE email = ?")
+        .bind(&email)
+        .fetch_optional(db)
+        .await?;
+    if email_taken.is_some() {
+        return Err(AppError::Conflict(format!(
+            "An account with this email already exists; sign in and link {} instead",
+            provider
+        )));
+    }
+
+    // Externally authenticated accounts get an unguessable password until the user sets one
+    let password_hash = passwords.hash(&utils::generate_session_token())?;
+    let preferred = identity.username.as_deref().unwrap_or("player");
+    let username = available_username(db, preferred).await?;
+
+    let mut user = User::new(email, username, password_hash);
+    user.avatar_url = identity.avatar_url.clone();
+    // The provider has already verified the address
+    user.email_verified_at = Some(chrono::Utc::now());
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO users (id, email, email_verified_at, username, password_hash, avatar_url)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&user.id)
+    .bind(&user.email)
+    .bind(user.email_verified_at)
+    .bind(&user.username)
+    .bind(&user.password_hash)
+    .bind(&user.avatar_url)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        if e.to_string().contains("Duplicate entry") {
+            AppError::Conflict("User with this email or username already exists".to_string())
+        } else {
+            AppError::Database(e)
+        }
+    })?;
+
+    sqlx::query(
+        "INSERT INTO user_identities (user_id, provider, provider_user_id, username, email, last_login_at)
+         VALUES (?, ?, ?, ?, ?, NOW())",
+    )
+    .bind(&user.id)
+    .bind(provider)
+    .bind(&identity.provider_user_id)
+    .bind(&identity.username)
+    .bind(&identity.email)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(user)
+}
+
+/// Derive a free username from the provider's, adding a numeric suffix on collision
+async fn available_username(db: &MySqlPool, preferred: &str) -> Result<String> {
+    let mut base: String = preferred
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '.')
+        .take(44)
+        .collect();
+    if base.len() < 3 {
+        base = format!("player{}", base);
+    }
+
+    for attempt in 0..5 {
+        let candidate = if attempt == 0 {
+            base.clone()
+        } else {
+            use rand::Rng;
+            format!("{}{}", base, rand::thread_rng().gen_range(1000..10000))
+        };
+
+        let taken: Option<(String,)> = sqlx::query_as("SELECT id FROM users WHERE username = ?")
+            .bind(&candidate)
+            .fetch_optional(db)
+            .await?;
+        if taken.is_none() {
+            return Ok(candidate);
+        }
+    }
+
+    Err(AppError::Conflict(
+        "Could not find an available username".to_string(),
+    ))
+}
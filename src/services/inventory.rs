@@ -0,0 +1,324 @@
+use sqlx::{mysql::MySqlArguments, query::QueryAs, MySql, MySqlPool};
+
+use crate::{
+    models::{
+        CreateItemRequest, GrantItemRequest, InventoryItem, ItemFilter, ItemGameRule,
+        SetItemGameRuleRequest, UserInventory,
+    },
+    services::games,
+    utils::{errors::Result, AppError},
+};
+
+/// Modifier tiers run from 1 to 5
+const MAX_MODIFIER_TIER: i32 = 5;
+
+/// Conditions on `inventory_items i` for an `ItemFilter`, bound by `bind_item_filter`.
+/// Items are eligible in their own game; elsewhere an `item_game_rules` row decides,
+/// and Bridge-wide items without a rule are eligible in every game.
+pub(crate) fn item_filter_sql() -> String {
+    let eligible_in = |flag: &str| {
+        format!(
+            "(? IS NULL OR EXISTS (SELECT 1 FROM games eg WHERE eg.name = ? AND (
+               i.game_id = eg.id
+               OR EXISTS (SELECT 1 FROM item_game_rules r
+                          WHERE r.item_id = i.id AND r.game_id = eg.id AND r.{} = true)
+               OR (i.game_id IS NULL AND NOT EXISTS (SELECT 1 FROM item_game_rules r
+                                                     WHERE r.item_id = i.id AND r.game_id = eg.id)))))",
+            flag
+        )
+    };
+
+    format!(
+        "(? IS NULL OR i.game_id = (SELECT og.id FROM games og WHERE og.name = ?))
+         AND (? IS NULL OR i.item_type = ?)
+         AND {}
+         AND {}",
+        eligible_in("can_use"),
+        eligible_in("can_show")
+    )
+}
+
+pub(crate) fn bind_item_filter<'q, O>(
+    query: QueryAs<'q, MySql, O, MySqlArguments>,
+    filter: &'q ItemFilter,
+) -> QueryAs<'q, MySql, O, MySqlArguments> {
+    query
+        .bind(filter.game.as_deref())
+        .bind(filter.game.as_deref())
+        .bind(filter.item_type.as_deref())
+        .bind(filter.item_type.as_deref())
+        .bind(filter.usable_in.as_deref())
+        .bind(filter.usable_in.as_deref())
+        .bind(filter.shown_in.as_deref())
+        .bind(filter.shown_in.as_deref())
+}
+
+/// Item definitions matching the filter, by name
+pub async fn list_items(db: &MySqlPool, filter: &ItemFilter) -> Result<Vec<InventoryItem>> {
+    let sql = format!(
+        "SELECT i.* FROM inventory_items i WHERE {} ORDER BY i.name",
+        item_filter_sql()
+    );
+
+    Ok(bind_item_filter(sqlx::query_as(&sql), filter)
+        .fetch_all(db)
+        .await?)
+}
+
+/// Add a new item definition to the catalog, in a game's namespace or Bridge-wide
+pub async fn create_item(db: &MySqlPool, request: &CreateItemRequest) -> Result<InventoryItem> {
+    if !request
+        .item_key
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(AppError::Validation(
+            "Item keys may only contain lowercase letters, digits, '-', '_' and '.'".to_string(),
+        ));
+    }
+
+    let game_id = match &request.game {
+        Some(name) => Some(games::find_active_game(db, name).await?.id),
+        None => None,
+    };
+
+    let rarity: Option<(i32,)> = sqlx::query_as("SELECT id FROM item_rarities WHERE id = ?")
+        .bind(request.rarity_id)
+        .fetch_optional(db)
+        .await?;
+    if rarity.is_none() {
+        return Err(AppError::bad_request("Unknown rarity"));
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO inventory_items
+         (game_id, item_key, name, description, item_type, rarity_id, base_credit_value,
+          max_modifiers, icon_url)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(game_id)
+    .bind(&request.item_key)
+    .bind(&request.name)
+    .bind(&request.description)
+    .bind(&request.item_type)
+    .bind(request.rarity_id)
+    .bind(request.base_credit_value)
+    .bind(request.max_modifiers)
+    .bind(&request.icon_url)
+    .execute(db)
+    .await
+    .map_err(|e| match e {
+        // Bridge-wide keys are kept unique by unique_global_item_key
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::Conflict(match game_id {
+                Some(_) => "An item with this key already exists in this game".to_string(),
+                None => "An item with this key already exists".to_string(),
+            })
+        }
+        e => AppError::Database(e),
+    })?;
+
+    let item = sqlx::query_as("SELECT * FROM inventory_items WHERE id = ?")
+        .bind(result.last_insert_id() as i32)
+        .fetch_one(db)
+        .await?;
+
+    Ok(item)
+}
+
+/// The name of the game an item belongs to, `None` for Bridge-wide items
+pub async fn item_game(db: &MySqlPool, item_id: i32) -> Result<Option<String>> {
+    let row: Option<(Option<String>,)> = sqlx::query_as(
+        "SELECT g.name FROM inventory_items i
+         LEFT JOIN games g ON g.id = i.game_id
+         WHERE i.id = ?",
+    )
+    .bind(item_id)
+    .fetch_optional(db)
+    .await?;
+
+    let (game,) = row.ok_or_else(|| AppError::not_found("Item"))?;
+
+    Ok(game)
+}
+
+/// Where an item may be used or shown besides its own game
+pub async fn list_item_game_rules(db: &MySqlPool, item_id: i32) -> Result<Vec<ItemGameRule>> {
+    find_item(db, item_id).await?;
+
+    Ok(sqlx::query_as(
+        "SELECT g.name AS game_name, r.can_use, r.can_show
+         FROM item_game_rules r
+         JOIN games g ON g.id = r.game_id
+         WHERE r.item_id = ?
+         ORDER BY g.name",
+    )
+    .bind(item_id)
+    .fetch_all(db)
+    .await?)
+}
+
+/// Allow or deny an item in another game, replacing any existing rule
+pub async fn set_item_game_rule(
+    db: &MySqlPool,
+    item_id: i32,
+    game_name: &str,
+    request: &SetItemGameRuleRequest,
+) -> Result<ItemGameRule> {
+    let item = find_item(db, item_id).await?;
+    let game = games::find_active_game(db, game_name).await?;
+
+    if item.game_id == Some(game.id) {
+        return Err(AppError::bad_request(
+            "Items are always eligible in their own game",
+        ));
+    }
+
+    sqlx::query(
+        "INSERT INTO item_game_rules (item_id, game_id, can_use, can_show) VALUES (?, ?, ?, ?)
+         ON DUPLICATE KEY UPDATE can_use = VALUES(can_use), can_show = VALUES(can_show)",
+    )
+    .bind(item.id)
+    .bind(game.id)
+    .bind(request.can_use)
+    .bind(request.can_show)
+    .execute(db)
+    .await?;
+
+    Ok(ItemGameRule {
+        game_name: game.name,
+        can_use: request.can_use,
+        can_show: request.can_show,
+    })
+}
+
+/// Returns false if the item had no rule for the game
+pub async fn remove_item_game_rule(db: &MySqlPool, item_id: i32, game_name: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "DELETE r FROM item_game_rules r
+         JOIN games g ON g.id = r.game_id
+         WHERE r.item_id = ? AND g.name = ?",
+    )
+    .bind(item_id)
+    .bind(game_name)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Item instances a user owns that match the filter, newest first
+pub async fn list_user_items(
+    db: &MySqlPool,
+    user_id: &str,
+    filter: &ItemFilter,
+) -> Result<Vec<UserInventory>> {
+    let sql = format!(
+        "SELECT ui.id, ui.user_id, ui.item_id, ui.acquired_at, ui.is_tradeable, ui.is_equipped,
+                ui.custom_name
+         FROM user_inventory ui
+         JOIN inventory_items i ON i.id = ui.item_id
+         WHERE ui.user_id = ? AND {}
+         ORDER BY ui.acquired_at DESC",
+        item_filter_sql()
+    );
+
+    let items = bind_item_filter(sqlx::query_as(&sql).bind(user_id), filter)
+        .fetch_all(db)
+        .await?;
+
+    Ok(items)
+}
+
+/// Put a new instance of an item, with any modifiers, into a user's inventory
+pub async fn grant_item(db: &MySqlPool, request: &GrantItemRequest) -> Result<UserInventory> {
+    let item = find_item(db, request.item_id).await?;
+
+    let user: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM users WHERE id = ? AND is_active = true")
+            .bind(&request.user_id)
+            .fetch_optional(db)
+            .await?;
+    if user.is_none() {
+        return Err(AppError::not_found("User"));
+    }
+
+    let modifiers = request.modifiers.as_deref().unwrap_or_default();
+    if modifiers.len() > item.max_modifiers as usize {
+        return Err(AppError::Validation(format!(
+            "{} allows at most {} modifiers",
+            item.name, item.max_modifiers
+        )));
+    }
+    if modifiers
+        .iter()
+        .any(|m| !(1..=MAX_MODIFIER_TIER).contains(&m.tier))
+    {
+        return Err(AppError::Validation(format!(
+            "Modifier tiers must be between 1 and {}",
+            MAX_MODIFIER_TIER
+        )));
+    }
+
+    let user_item = UserInventory::new(request.user_id.clone(), item.id);
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO user_inventory (id, user_id, item_id, acquired_at, is_tradeable, is_equipped)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&user_item.id)
+    .bind(&user_item.user_id)
+    .bind(user_item.item_id)
+    .bind(user_item.acquired_at)
+    .bind(user_item.is_tradeable)
+    .bind(user_item.is_equipped)
+    .execute(&mut *tx)
+    .await?;
+
+    for modifier in modifiers {
+        let known: Option<(i32,)> = sqlx::query_as("SELECT id FROM item_modifiers WHERE id = ?")
+            .bind(modifier.modifier_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if known.is_none() {
+            return Err(AppError::bad_request("Unknown modifier"));
+        }
+
+        sqlx::query(
+            "INSERT INTO user_item_modifiers (user_item_id, modifier_id, modifier_tier)
+             VALUES (?, ?, ?)",
+        )
+        .bind(&user_item.id)
+        .bind(modifier.modifier_id)
+        .bind(modifier.tier)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                AppError::bad_request("The same modifier can only be applied once")
+            }
+            e => AppError::Database(e),
+        })?;
+    }
+
+    tx.commit().await?;
+
+    tracing::info!(
+        user_id = %user_item.user_id,
+        item_id = user_item.item_id,
+        user_item_id = %user_item.id,
+        "Item granted"
+    );
+
+    Ok(user_item)
+}
+
+async fn find_item(db: &MySqlPool, item_id: i32) -> Result<InventoryItem> {
+    sqlx::query_as("SELECT * FROM inventory_items WHERE id = ?")
+        .bind(item_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::not_found("Item"))
+}
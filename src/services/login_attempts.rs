@@ -0,0 +1,246 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::MySqlPool;
+
+use crate::{
+    config::LoginThrottleConfig,
+    utils::{errors::Result, AppError},
+};
+
+/// What happened to a login attempt, as recorded in `login_attempts.outcome`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoginOutcome {
+    Success,
+    /// Password accepted, waiting for the second factor
+    MfaRequired,
+    InvalidPassword,
+    InvalidMfaCode,
+    UnknownEmail,
+    /// Refused without checking credentials because of backoff
+    Locked,
+}
+
+impl std::fmt::Display for LoginOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            LoginOutcome::Success => "success",
+            LoginOutcome::MfaRequired => "mfa_required",
+            LoginOutcome::InvalidPassword => "invalid_password",
+            LoginOutcome::InvalidMfaCode => "invalid_mfa_code",
+            LoginOutcome::UnknownEmail => "unknown_email",
+            LoginOutcome::Locked => "locked",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+/// A table of attempts at guessing a secret, with the columns backoff is counted by
+pub(crate) struct AttemptLog {
+    pub table: &'static str,
+    /// Column naming the account the guesses are aimed at
+    pub account_column: &'static str,
+    /// Condition matching the attempts that count as failures
+    pub counted_failures: &'static str,
+}
+
+const LOGIN_ATTEMPTS: AttemptLog = AttemptLog {
+    table: "login_attempts",
+    account_column: "email",
+    // Only wrong guesses count towards backoff; refused attempts don't extend a lockout
+    counted_failures: "outcome IN ('invalid_password', 'invalid_mfa_code', 'unknown_email')",
+};
+
+pub async fn record(
+    db: &MySqlPool,
+    email: &str,
+    user_id: Option<&str>,
+    user_agent: Option<&str>,
+    ip_address: Option<&str>,
+    outcome: LoginOutcome,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO login_attempts (email, user_id, ip_address, user_agent, success, outcome, attempted_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(email)
+    .bind(user_id)
+    .bind(ip_address)
+    .bind(user_agent)
+    .bind(outcome == LoginOutcome::Success)
+    .bind(outcome.to_string())
+    .bind(Utc::now())
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Refuse the attempt while the account or the client IP is backing off after
+/// repeated failures
+pub async fn check(
+    db: &MySqlPool,
+    config: &LoginThrottleConfig,
+    email: &str,
+    ip_address: Option<&str>,
+) -> Result<()> {
+    match backoff_remaining(db, config, &LOGIN_ATTEMPTS, email, ip_address).await? {
+        Some(remaining) => Err(AppError::TooManyRequests {
+            message: "Too many failed login attempts, try again later".to_string(),
+            retry_after: remaining.num_seconds().max(1) as u64,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Time left while `account` or `ip_address` is backing off after repeated
+/// failures recorded in `log`
+pub(crate) async fn backoff_remaining(
+    db: &MySqlPool,
+    config: &LoginThrottleConfig,
+    log: &AttemptLog,
+    account: &str,
+    ip_address: Option<&str>,
+) -> Result<Option<Duration>> {
+    let now = Utc::now();
+
+    // A success clears the account's failure count
+    let (last_success,): (Option<DateTime<Utc>>,) = sqlx::query_as(&format!(
+        "SELECT MAX(attempted_at) FROM {} WHERE {} = ? AND success = true",
+        log.table, log.account_column
+    ))
+    .bind(account)
+    .fetch_one(db)
+    .await?;
+    let since = last_success.map_or(now - config.window, |last| last.max(now - config.window));
+
+    let (failures, last_failure): (i64, Option<DateTime<Utc>>) = sqlx::query_as(&format!(
+        "SELECT COUNT(*), MAX(attempted_at) FROM {}
+         WHERE {} = ? AND attempted_at > ? AND {}",
+        log.table, log.account_column, log.counted_failures
+    ))
+    .bind(account)
+    .bind(since)
+    .fetch_one(db)
+    .await?;
+
+    let mut remaining = lockout_remaining(
+        config,
+        failures,
+        config.max_account_failures,
+        last_failure,
+        now,
+    );
+
+    // Successes don't reset the IP count, otherwise one valid account could be used
+    // to keep guessing at others
+    if let Some(ip_address) = ip_address {
+        let (failures, last_failure): (i64, Option<DateTime<Utc>>) = sqlx::query_as(&format!(
+            "SELECT COUNT(*), MAX(attempted_at) FROM {}
+             WHERE ip_address = ? AND attempted_at > ? AND {}",
+            log.table, log.counted_failures
+        ))
+        .bind(ip_address)
+        .bind(now - config.window)
+        .fetch_one(db)
+        .await?;
+
+        remaining = remaining.max(lockout_remaining(
+            config,
+            failures,
+            config.max_ip_failures,
+            last_failure,
+            now,
+        ));
+    }
+
+    Ok(remaining)
+}
+
+/// Time left before another attempt is allowed. The delay starts at `base_delay`
+/// once `max_failures` is reached and doubles with every further failure.
+fn lockout_remaining(
+    config: &LoginThrottleConfig,
+    failures: i64,
+    max_failures: i64,
+    last_failure: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    let last_failure = last_failure?;
+    if failures < max_failures {
+        return None;
+    }
+
+    let doublings = (failures - max_failures).min(20) as i32;
+    let delay = (config.base_delay * 2i32.pow(doublings as u32)).min(config.max_delay);
+    let remaining = last_failure + delay - now;
+
+    (remaining > Duration::zero()).then_some(remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LoginThrottleConfig {
+        LoginThrottleConfig {
+            max_account_failures: 5,
+            max_ip_failures: 20,
+            window: Duration::minutes(15),
+            base_delay: Duration::seconds(30),
+            max_delay: Duration::minutes(15),
+        }
+    }
+
+    fn remaining_after(failures: i64, elapsed: Duration) -> Option<Duration> {
+        let now = Utc::now();
+        lockout_remaining(&config(), failures, 5, Some(now - elapsed), now)
+    }
+
+    #[test]
+    fn no_lockout_below_the_threshold() {
+        assert_eq!(remaining_after(4, Duration::zero()), None);
+        assert_eq!(remaining_after(0, Duration::zero()), None);
+    }
+
+    #[test]
+    fn no_lockout_without_a_recorded_failure() {
+        assert_eq!(lockout_remaining(&config(), 10, 5, None, Utc::now()), None);
+    }
+
+    #[test]
+    fn delay_starts_at_base_and_doubles() {
+        assert_eq!(
+            remaining_after(5, Duration::zero()),
+            Some(Duration::seconds(30))
+        );
+        assert_eq!(
+            remaining_after(6, Duration::zero()),
+            Some(Duration::seconds(60))
+        );
+        assert_eq!(
+            remaining_after(7, Duration::zero()),
+            Some(Duration::seconds(120))
+        );
+    }
+
+    #[test]
+    fn delay_is_capped_at_max() {
+        assert_eq!(
+            remaining_after(10, Duration::zero()),
+            Some(Duration::minutes(15))
+        );
+        assert_eq!(
+            remaining_after(1_000, Duration::zero()),
+            Some(Duration::minutes(15))
+        );
+    }
+
+    #[test]
+    fn remaining_counts_down_from_the_last_failure() {
+        assert_eq!(
+            remaining_after(6, Duration::seconds(45)),
+            Some(Duration::seconds(15))
+        );
+        assert_eq!(remaining_after(6, Duration::seconds(60)), None);
+        assert_eq!(remaining_after(6, Duration::minutes(5)), None);
+    }
+}
@@ -0,0 +1,155 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    config::{MailConfig, MailTransport},
+    utils::errors::{AppError, Result},
+};
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing email delivery
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<()>;
+}
+
+/// Build the mailer selected by configuration
+pub fn from_config(config: &MailConfig) -> Result<Arc<dyn Mailer>> {
+    let mailer: Arc<dyn Mailer> = match &config.transport {
+        MailTransport::Smtp {
+            host,
+            port,
+            username,
+            password,
+            tls,
+        } => Arc::new(SmtpMailer::new(
+            host,
+            *port,
+            username.clone().zip(password.clone()),
+            tls,
+            &config.from,
+        )?),
+        MailTransport::Log { path } => Arc::new(LogMailer::new(path.clone())),
+    };
+
+    Ok(mailer)
+}
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(
+        host: &str,
+        port: u16,
+        credentials: Option<(String, String)>,
+        tls: &str,
+        from: &str,
+    ) -> Result<Self> {
+        let smtp_error = |e: lettre::transport::smtp::Error| {
+            AppError::Internal(format!("Invalid SMTP configuration: {}", e))
+        };
+
+        let mut builder = match tls {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host).map_err(smtp_error)?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host).map_err(smtp_error)?,
+        }
+        .port(port);
+
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let from = from
+            .parse()
+            .map_err(|e| AppError::Internal(format!("Invalid MAIL_FROM address: {}", e)))?;
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let to: Mailbox = email
+            .to
+            .parse()
+            .map_err(|e| AppError::BadRequest(format!("Invalid recipient address: {}", e)))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body)
+            .map_err(|e| AppError::Internal(format!("Failed to build email: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+/// Development mailer that appends messages to a file and the application log
+pub struct LogMailer {
+    path: String,
+}
+
+impl LogMailer {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        tracing::info!(to = %email.to, subject = %email.subject, "Email (log mailer)");
+
+        if let Some(parent) = std::path::Path::new(&self.path).parent() {
+            tokio::fs::create_dir_all(parent).await.ok();
+        }
+
+        let entry = format!(
+            "To: {}\nSubject: {}\nDate: {}\n\n{}\n\n----\n",
+            email.to,
+            email.subject,
+            chrono::Utc::now().to_rfc3339(),
+            email.body
+        );
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to open mail log: {}", e)))?;
+
+        file.write_all(entry.as_bytes())
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to write mail log: {}", e)))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use sqlx::MySqlPool;
+
+use crate::{
+    models::{
+        AuctionBid, ItemFilter, ListingType, MarketplaceFilter, MarketplaceListingSummary,
+        MarketplaceListingsResponse,
+    },
+    services::{
+        blocks::{self, Interaction},
+        inventory, profiles,
+    },
+    utils::{errors::Result, pagination, AppError},
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 50;
+
+/// Listings matching the filter, newest first. Browsing shows active listings
+/// the viewer may see: public ones and those for clans they belong to, leaving
+/// out sellers blocked by or blocking the viewer. With `mine` set, only the
+/// viewer's own listings are returned, whatever their status.
+pub async fn list_listings(
+    db: &MySqlPool,
+    viewer_id: &str,
+    filter: &MarketplaceFilter,
+    mine: bool,
+) -> Result<MarketplaceListingsResponse> {
+    let bounds = pagination::page_bounds(
+        filter.page,
+        filter.per_page,
+        DEFAULT_PAGE_SIZE,
+        MAX_PAGE_SIZE,
+    );
+
+    let scope = if mine {
+        "l.seller_id = ?".to_string()
+    } else {
+        format!(
+            "l.status = 'active' AND l.expires_at > NOW()
+             AND u.is_active = true AND u.deleted_at IS NULL
+             AND (l.visibility = 'public'
+               OR EXISTS (SELECT 1 FROM clan_members m WHERE m.clan_id = l.clan_id AND m.user_id = ?))
+             AND {}",
+            blocks::not_blocked_sql("l.seller_id")
+        )
+    };
+
+    let sql = format!(
+        "SELECT l.id, l.seller_id, u.username AS seller_username, l.user_item_id,
+                i.id AS item_id, i.name AS item_name, i.item_type, i.rarity_id, g.name AS game,
+                l.price, l.listing_type, l.visibility, l.clan_id, l.status, l.created_at,
+                l.expires_at
+         FROM marketplace_listings l
+         JOIN users u ON u.id = l.seller_id
+         JOIN user_inventory ui ON ui.id = l.user_item_id
+         JOIN inventory_items i ON i.id = ui.item_id
+         LEFT JOIN games g ON g.id = i.game_id
+         WHERE {} AND {}
+           AND (? IS NULL OR i.rarity_id = ?)
+           AND (? IS NULL OR l.price >= ?)
+           AND (? IS NULL OR l.price <= ?)
+           AND (? IS NULL OR l.listing_type = ?)
+           AND (? IS NULL OR l.visibility = ?)
+           AND (? IS NULL OR l.clan_id = ?)
+           AND (? IS NULL OR i.name LIKE ? OR ui.custom_name LIKE ?)
+         ORDER BY l.created_at DESC
+         LIMIT ? OFFSET ?",
+        scope,
+        inventory::item_filter_sql()
+    );
+
+    let items = ItemFilter {
+        game: filter.game.clone(),
+        item_type: filter.item_type.clone(),
+        usable_in: filter.usable_in.clone(),
+        shown_in: None,
+    };
+    let listing_type = filter.listing_type.as_ref().map(|t| t.to_string());
+    let visibility = filter.visibility.as_ref().map(|v| v.to_string());
+    let search_term = filter
+        .search_term
+        .as_deref()
+        .map(str::trim)
+        .filter(|term| !term.is_empty());
+    let contains = search_term.map(|term| format!("%{}%", profiles::escape_like(term)));
+
+    let mut query = sqlx::query_as::<_, MarketplaceListingSummary>(&sql).bind(viewer_id);
+    if !mine {
+        query = query.bind(viewer_id).bind(viewer_id);
+    }
+
+    let mut results = inventory::bind_item_filter(query, &items)
+        .bind(filter.rarity_id)
+        .bind(filter.rarity_id)
+        .bind(filter.min_price)
+        .bind(filter.min_price)
+        .bind(filter.max_price)
+        .bind(filter.max_price)
+        .bind(&listing_type)
+        .bind(&listing_type)
+        .bind(&visibility)
+        .bind(&visibility)
+        .bind(&filter.clan_id)
+        .bind(&filter.clan_id)
+        .bind(search_term)
+        .bind(&contains)
+        .bind(&contains)
+        .bind(bounds.limit)
+        .bind(bounds.offset)
+        .fetch_all(db)
+        .await?;
+
+    let has_more = pagination::take_page(&mut results, bounds);
+
+    Ok(MarketplaceListingsResponse {
+        results,
+        page: bounds.page,
+        per_page: bounds.per_page,
+        has_more,
+    })
+}
+
+/// Bid on an active auction the bidder can see. The first bid must meet the
+/// listing price and every later one must beat the highest bid so far.
+pub async fn place_bid(
+    db: &MySqlPool,
+    bidder_id: &str,
+    listing_id: &str,
+    bid_amount: i32,
+) -> Result<AuctionBid> {
+    let mut tx = db.begin().await?;
+
+    let listing: Option<(String, String, i32)> = sqlx::query_as(
+        "SELECT l.seller_id, l.listing_type, l.price FROM marketplace_listings l
+         WHERE l.id = ? AND l.status = 'active' AND l.expires_at > NOW()
+           AND (l.visibility = 'public'
+             OR EXISTS (SELECT 1 FROM clan_members m WHERE m.clan_id = l.clan_id AND m.user_id = ?))
+         FOR UPDATE",
+    )
+    .bind(listing_id)
+    .bind(bidder_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((seller_id, listing_type, price)) = listing else {
+        return Err(AppError::not_found("Listing"));
+    };
+
+    if ListingType::from(listing_type) != ListingType::Auction {
+        return Err(AppError::bad_request("Only auctions take bids"));
+    }
+    if seller_id == bidder_id {
+        return Err(AppError::bad_request("You can't bid on your own listing"));
+    }
+
+    blocks::ensure_can_interact(db, bidder_id, &seller_id, Interaction::Bid).await?;
+
+    let (highest,): (Option<i32>,) =
+        sqlx::query_as("SELECT MAX(bid_amount) FROM auction_bids WHERE listing_id = ?")
+            .bind(listing_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    match highest {
+        Some(highest) if bid_amount <= highest => {
+            return Err(AppError::Validation(format!(
+                "Bid must be higher than the current bid of {}",
+                highest
+            )));
+        }
+        None if bid_amount < price => {
+            return Err(AppError::Validation(format!(
+                "Bid must be at least the starting price of {}",
+                price
+            )));
+        }
+        _ => {}
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO auction_bids (listing_id, bidder_id, bid_amount) VALUES (?, ?, ?)",
+    )
+    .bind(listing_id)
+    .bind(bidder_id)
+    .bind(bid_amount)
+    .execute(&mut *tx)
+    .await?;
+
+    let bid: AuctionBid = sqlx::query_as("SELECT * FROM auction_bids WHERE id = ?")
+        .bind(result.last_insert_id())
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(bid)
+}
@@ -0,0 +1,377 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sqlx::MySqlPool;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::utils::{self, errors::Result, AppError};
+
+/// Name shown next to the account in authenticator apps
+const TOTP_ISSUER: &str = "Bridge";
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECONDS: u64 = 30;
+/// Accept codes from one step either side of now to allow for clock drift
+const TOTP_SKEW_STEPS: i64 = 1;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Unambiguous characters only (no 0/o, 1/l/i)
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+/// Whether the user has a confirmed TOTP authenticator
+pub async fn is_enabled(db: &MySqlPool, user_id: &str) -> Result<bool> {
+    Ok(enabled_at(db, user_id).await?.is_some())
+}
+
+pub async fn enabled_at(db: &MySqlPool, user_id: &str) -> Result<Option<DateTime<Utc>>> {
+    let enabled: Option<(Option<DateTime<Utc>>,)> =
+        sqlx::query_as("SELECT enabled_at FROM user_totp WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(db)
+            .await?;
+
+    Ok(enabled.and_then(|(enabled_at,)| enabled_at))
+}
+
+/// Mark a login challenge as used. Returns false if it already completed a login.
+pub async fn consume_challenge(
+    db: &MySqlPool,
+    jti: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<bool> {
+    sqlx::query("DELETE FROM used_mfa_challenges WHERE expires_at < NOW()")
+        .execute(db)
+        .await?;
+
+    let result = sqlx::query("INSERT INTO used_mfa_challenges (jti, expires_at) VALUES (?, ?)")
+        .bind(jti)
+        .bind(expires_at)
+        .execute(db)
+        .await;
+
+    match result {
+        Ok(_) => Ok(true),
+        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => Ok(false),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+/// Generate a new pending secret, replacing any unconfirmed one
+pub async fn begin_enrollment(
+    db: &MySqlPool,
+    user_id: &str,
+    account_name: &str,
+) -> Result<TotpEnrollment> {
+    if is_enabled(db, user_id).await? {
+        return Err(AppError::Conflict(
+            "Two-factor authentication is already enabled".to_string(),
+        ));
+    }
+
+    let secret = match Secret::generate_secret().to_encoded() {
+        Secret::Encoded(secret) => secret,
+        Secret::Raw(_) => unreachable!("to_encoded always returns an encoded secret"),
+    };
+    let provisioning_uri = totp(&secret, account_name)?.get_url();
+
+    sqlx::query(
+        "INSERT INTO user_totp (user_id, secret) VALUES (?, ?)
+         ON DUPLICATE KEY UPDATE secret = VALUES(secret), last_used_step = NULL",
+    )
+    .bind(user_id)
+    .bind(&secret)
+    .execute(db)
+    .await?;
+
+    Ok(TotpEnrollment {
+        secret,
+        provisioning_uri,
+    })
+}
+
+/// Turn on two-factor authentication once the user proves their authenticator works.
+/// Returns a fresh set of recovery codes.
+pub async fn confirm_enrollment(db: &MySqlPool, user_id: &str, code: &str) -> Result<Vec<String>> {
+    let pending: Option<(String, Option<DateTime<Utc>>)> =
+        sqlx::query_as("SELECT secret, enabled_at FROM user_totp WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(db)
+            .await?;
+
+    match pending {
+        None => {
+            return Err(AppError::bad_request(
+                "Start two-factor setup before confirming it",
+            ))
+        }
+        Some((_, Some(_))) => {
+            return Err(AppError::Conflict(
+                "Two-factor authentication is already enabled".to_string(),
+            ))
+        }
+        Some((secret, None)) => {
+            if !check_totp(db, user_id, &secret, code).await? {
+                return Err(AppError::bad_request("Invalid two-factor code"));
+            }
+        }
+    }
+
+    sqlx::query("UPDATE user_totp SET enabled_at = NOW() WHERE user_id = ?")
+        .bind(user_id)
+        .execute(db)
+        .await?;
+
+    regenerate_recovery_codes(db, user_id).await
+}
+
+pub async fn disable(db: &MySqlPool, user_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+        .bind(user_id)
+        .execute(db)
+        .await?;
+
+    sqlx::query("DELETE FROM user_totp WHERE user_id = ?")
+        .bind(user_id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Check a second factor for a user with 2FA enabled: a current TOTP code or,
+/// failing that, an unused recovery code (which is consumed)
+pub async fn verify(db: &MySqlPool, user_id: &str, code: &str) -> Result<bool> {
+    if verify_totp(db, user_id, code).await? {
+        return Ok(true);
+    }
+
+    consume_recovery_code(db, user_id, code).await
+}
+
+/// Check a TOTP code against the user's confirmed authenticator. Each code is accepted once.
+pub async fn verify_totp(db: &MySqlPool, user_id: &str, code: &str) -> Result<bool> {
+    let secret: Option<(String,)> =
+        sqlx::query_as("SELECT secret FROM user_totp WHERE user_id = ? AND enabled_at IS NOT NULL")
+            .bind(user_id)
+            .fetch_optional(db)
+            .await?;
+
+    match secret {
+        Some((secret,)) => check_totp(db, user_id, &secret, code).await,
+        None => Ok(false),
+    }
+}
+
+/// Replace all recovery codes with a new set, returning the plaintext codes
+pub async fn regenerate_recovery_codes(db: &MySqlPool, user_id: &str) -> Result<Vec<String>> {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for code in &codes {
+        sqlx::query("INSERT INTO user_recovery_codes (id, user_id, code_hash) VALUES (?, ?, ?)")
+            .bind(Uuid::new_v4().to_string())
+            .bind(user_id)
+            .bind(utils::hash_token(&normalize_recovery_code(code)))
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(codes)
+}
+
+pub async fn recovery_codes_remaining(db: &MySqlPool, user_id: &str) -> Result<i64> {
+    let (remaining,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM user_recovery_codes WHERE user_id = ? AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(remaining)
+}
+
+async fn consume_recovery_code(db: &MySqlPool, user_id: &str, code: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE user_recovery_codes SET used_at = NOW()
+         WHERE user_id = ? AND code_hash = ? AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .bind(utils::hash_token(&normalize_recovery_code(code)))
+    .execute(db)
+    .await?;
+
+    if result.rows_affected() > 0 {
+        tracing::info!(user_id = %user_id, "Recovery code used");
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// Match `code` against the steps around now and record the matching step so the
+/// same code can't be replayed
+async fn check_totp(db: &MySqlPool, user_id: &str, secret: &str, code: &str) -> Result<bool> {
+    let totp = totp(secret, "")?;
+    let Some(step) = matching_step(&totp, code, Utc::now().timestamp()) else {
+        return Ok(false);
+    };
+
+    let result = sqlx::query(
+        "UPDATE user_totp SET last_used_step = ?
+         WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)",
+    )
+    .bind(step)
+    .bind(user_id)
+    .bind(step)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// The step within `TOTP_SKEW_STEPS` of `now` (a Unix timestamp) whose code is `code`
+fn matching_step(totp: &TOTP, code: &str, now: i64) -> Option<i64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != TOTP_DIGITS || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let current_step = now / TOTP_STEP_SECONDS as i64;
+
+    (-TOTP_SKEW_STEPS..=TOTP_SKEW_STEPS)
+        .map(|offset| current_step + offset)
+        .find(|step| totp.check(&code, *step as u64 * TOTP_STEP_SECONDS))
+}
+
+fn totp(secret: &str, account_name: &str) -> Result<TOTP> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| AppError::Internal(format!("Invalid TOTP secret: {}", e)))?;
+
+    // Skew is applied by check_totp so it knows which step matched
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        0,
+        TOTP_STEP_SECONDS,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        account_name.to_string(),
+    )
+    .map_err(|e| AppError::Internal(format!("Invalid TOTP configuration: {}", e)))
+}
+
+/// Ten characters split as `xxxxx-xxxxx`
+fn generate_recovery_code() -> String {
+    let mut rng = rand::thread_rng();
+    let chars: String = (0..10)
+        .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+        .collect();
+
+    format!("{}-{}", &chars[..5], &chars[5..])
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Base32 of the RFC 6238 SHA-1 test secret "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    /// RFC 6238 appendix B SHA-1 vectors, truncated to our six digits
+    const RFC_VECTORS: &[(u64, &str)] = &[
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1111111111, "050471"),
+        (1234567890, "005924"),
+        (2000000000, "279037"),
+        (20000000000, "353130"),
+    ];
+
+    fn rfc_totp() -> TOTP {
+        totp(RFC_SECRET, "").unwrap()
+    }
+
+    #[test]
+    fn generates_rfc_6238_codes() {
+        let totp = rfc_totp();
+        for (time, code) in RFC_VECTORS {
+            assert_eq!(totp.generate(*time), *code, "at T = {}", time);
+        }
+    }
+
+    #[test]
+    fn matches_rfc_6238_codes_at_their_own_step() {
+        let totp = rfc_totp();
+        for (time, code) in RFC_VECTORS {
+            let expected = *time as i64 / TOTP_STEP_SECONDS as i64;
+            assert_eq!(matching_step(&totp, code, *time as i64), Some(expected));
+        }
+    }
+
+    #[test]
+    fn accepts_codes_one_step_either_side() {
+        let totp = rfc_totp();
+        // 1111111109 and 1111111111 sit in neighbouring steps
+        let earlier = 1111111109 / TOTP_STEP_SECONDS as i64;
+
+        assert_eq!(matching_step(&totp, "081804", 1111111111), Some(earlier));
+        assert_eq!(
+            matching_step(&totp, "050471", 1111111109),
+            Some(earlier + 1)
+        );
+    }
+
+    #[test]
+    fn rejects_codes_outside_the_window() {
+        let totp = rfc_totp();
+        let step = TOTP_STEP_SECONDS as i64;
+
+        assert_eq!(matching_step(&totp, "287082", 59 + 2 * step), None);
+        assert_eq!(matching_step(&totp, "279037", 2000000000 - 2 * step), None);
+    }
+
+    #[test]
+    fn ignores_whitespace_and_rejects_malformed_codes() {
+        let totp = rfc_totp();
+
+        assert_eq!(matching_step(&totp, "287 082", 59), Some(1));
+        assert_eq!(matching_step(&totp, "28708", 59), None);
+        assert_eq!(matching_step(&totp, "2870820", 59), None);
+        assert_eq!(matching_step(&totp, "28708a", 59), None);
+        assert_eq!(matching_step(&totp, "", 59), None);
+    }
+
+    #[test]
+    fn recovery_codes_normalize_to_their_generated_form() {
+        let code = generate_recovery_code();
+        assert_eq!(code.len(), 11);
+        assert_eq!(&code[5..6], "-");
+        assert_eq!(normalize_recovery_code(&code), code.replace('-', ""));
+        assert_eq!(
+            normalize_recovery_code(" ABCDE-fgh23 "),
+            normalize_recovery_code("abcde fgh23")
+        );
+    }
+}
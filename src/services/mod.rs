@@ -1 +1,17 @@
-// Services module - Business logic will be implemented here
+pub mod accounts;
+pub mod blocks;
+pub mod clans;
+pub mod game_clients;
+pub mod games;
+pub mod identities;
+pub mod inventory;
+pub mod login_attempts;
+pub mod mailer;
+pub mod marketplace;
+pub mod mfa;
+pub mod profiles;
+pub mod roles;
+pub mod sessions;
+pub mod social;
+pub mod tokens;
+pub mod trading;
@@ -0,0 +1,410 @@
+use sqlx::MySqlPool;
+
+use crate::{
+    models::{
+        PrivacySettings, ProfileClan, ProfileGame, ProfileVisibility, PublicProfile, ShowcaseItem,
+        User, UserSearchQuery, UserSearchResponse, UserSummary,
+    },
+    services::{blocks, social},
+    utils::{errors::Result, pagination, AppError},
+};
+
+const DEFAULT_SEARCH_PAGE_SIZE: i64 = 20;
+const MAX_SEARCH_PAGE_SIZE: i64 = 50;
+/// Shorter queries match too much of the user base to be useful
+const MIN_SEARCH_QUERY_LENGTH: usize = 2;
+
+/// How the viewer of a profile is related to its owner
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Relationship {
+    pub is_self: bool,
+    pub friend: bool,
+    pub clanmate: bool,
+}
+
+impl Relationship {
+    pub fn can_see(&self, visibility: ProfileVisibility) -> bool {
+        self.is_self
+            || match visibility {
+                ProfileVisibility::Everyone => true,
+                ProfileVisibility::Friends => self.friend,
+                ProfileVisibility::Clan => self.clanmate,
+                ProfileVisibility::FriendsAndClan => self.friend || self.clanmate,
+                ProfileVisibility::Private => false,
+            }
+    }
+}
+
+pub async fn privacy_settings(db: &MySqlPool, user_id: &str) -> Result<PrivacySettings> {
+    let row: Option<(String, String, String)> = sqlx::query_as(
+        "SELECT clan_visibility, games_visibility, showcase_visibility
+         FROM user_privacy_settings WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(match row {
+        Some((clan, games, showcase)) => PrivacySettings {
+            clan: clan.into(),
+            games: games.into(),
+            showcase: showcase.into(),
+        },
+        None => PrivacySettings::default(),
+    })
+}
+
+pub async fn update_privacy_settings(
+    db: &MySqlPool,
+    user_id: &str,
+    settings: &PrivacySettings,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO user_privacy_settings
+         (user_id, clan_visibility, games_visibility, showcase_visibility)
+         VALUES (?, ?, ?, ?)
+         ON DUPLICATE KEY UPDATE clan_visibility = VALUES(clan_visibility),
+         games_visibility = VALUES(games_visibility),
+         showcase_visibility = VALUES(showcase_visibility)",
+    )
+    .bind(user_id)
+    .bind(settings.clan.to_string())
+    .bind(settings.games.to_string())
+    .bind(settings.showcase.to_string())
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn are_clanmates(db: &MySqlPool, user_id: &str, other_id: &str) -> Result<bool> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM clan_members a
+         JOIN clan_members b ON b.clan_id = a.clan_id
+         JOIN clans c ON c.id = a.clan_id AND c.is_active = true
+         WHERE a.user_id = ? AND b.user_id = ?",
+    )
+    .bind(user_id)
+    .bind(other_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(count > 0)
+}
+
+pub async fn relationship(db: &MySqlPool, viewer_id: &str, owner_id: &str) -> Result<Relationship> {
+    if viewer_id == owner_id {
+        return Ok(Relationship {
+            is_self: true,
+            ..Default::default()
+        });
+    }
+
+    Ok(Relationship {
+        is_self: false,
+        friend: social::are_friends(db, viewer_id, owner_id).await?,
+        clanmate: are_clanmates(db, viewer_id, owner_id).await?,
+    })
+}
+
+/// The profile of `user_id` as `viewer_id` is allowed to see it
+pub async fn public_profile(
+    db: &MySqlPool,
+    viewer_id: &str,
+    user_id: &str,
+) -> Result<PublicProfile> {
+    let user: User = sqlx::query_as(
+        "SELECT * FROM users WHERE id = ? AND is_active = true AND deleted_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| AppError::not_found("User"))?;
+
+    // Blocked users can't see each other at all
+    if blocks::is_blocked_between(db, viewer_id, user_id).await? {
+        return Err(AppError::not_found("User"));
+    }
+
+    let settings = privacy_settings(db, user_id).await?;
+    let viewer = relationship(db, viewer_id, user_id).await?;
+
+    let clan: Option<ProfileClan> = if viewer.can_see(settings.clan) {
+        sqlx::query_as(
+            "SELECT c.id, c.name, c.tag, m.`rank` FROM clan_members m
+             JOIN clans c ON c.id = m.clan_id
+             WHERE m.user_id = ? AND c.is_active = true
+             ORDER BY m.joined_at
+             LIMIT 1",
+        )
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?
+    } else {
+        None
+    };
+
+    let games = if viewer.can_see(settings.games) {
+        Some(
+            sqlx::query_as::<_, ProfileGame>(
+                "SELECT game_name, linked_at FROM user_games WHERE user_id = ? ORDER BY linked_at",
+            )
+            .bind(user_id)
+            .fetch_all(db)
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    let showcase = if viewer.can_see(settings.showcase) {
+        Some(
+            sqlx::query_as::<_, ShowcaseItem>(
+                "SELECT ui.id, i.name, ui.custom_name, i.item_type, r.name AS rarity, i.icon_url
+                 FROM user_inventory ui
+                 JOIN inventory_items i ON i.id = ui.item_id
+                 JOIN item_rarities r ON r.id = i.rarity_id
+                 WHERE ui.user_id = ? AND ui.is_equipped = true
+                 ORDER BY ui.acquired_at",
+            )
+            .bind(user_id)
+            .fetch_all(db)
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    Ok(PublicProfile {
+        id: user.id,
+        username: user.username,
+        avatar_url: user.avatar_url,
+        joined_at: user.created_at,
+        clan,
+        games,
+        showcase,
+    })
+}
+
+/// Find players by username and/or linked game account. Usernames match exactly,
+/// by prefix, by substring or by sound (`SOUNDEX`), ranked in that order. Game
+/// lookups only match players whose linked games are visible to the searcher.
+/// Inactive and deleted accounts, the searcher and anyone blocked by or blocking
+/// the searcher are left out.
+pub async fn search_users(
+    db: &MySqlPool,
+    searcher_id: &str,
+    query: &UserSearchQuery,
+) -> Result<UserSearchResponse> {
+    let q = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let game = query
+        .game
+        .as_deref()
+        .map(str::trim)
+        .filter(|game| !game.is_empty());
+    let game_user_id = query
+        .game_user_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty());
+
+    if q.is_none() && game.is_none() {
+        return Err(AppError::bad_request("Provide a search query or a game"));
+    }
+    if game_user_id.is_some() && game.is_none() {
+        return Err(AppError::bad_request(
+            "Searching by game account ID needs a game",
+        ));
+    }
+    if q.is_some_and(|q| q.chars().count() < MIN_SEARCH_QUERY_LENGTH) {
+        return Err(AppError::Validation(format!(
+            "Search query must be at least {} characters",
+            MIN_SEARCH_QUERY_LENGTH
+        )));
+    }
+
+    let bounds = pagination::page_bounds(
+        query.page,
+        query.per_page,
+        DEFAULT_SEARCH_PAGE_SIZE,
+        MAX_SEARCH_PAGE_SIZE,
+    );
+
+    let escaped = q.map(escape_like);
+    let prefix = escaped.as_ref().map(|q| format!("{}%", q));
+    let contains = escaped.as_ref().map(|q| format!("%{}%", q));
+    let default_games_visibility = PrivacySettings::default().games.to_string();
+
+    let sql = format!(
+        "SELECT u.id, u.username, u.avatar_url FROM users u
+         LEFT JOIN user_privacy_settings p ON p.user_id = u.id
+         WHERE u.is_active = true AND u.deleted_at IS NULL AND u.id != ?
+           AND (? IS NULL OR u.username LIKE ? OR SOUNDEX(u.username) = SOUNDEX(?))
+           AND (? IS NULL OR (
+             EXISTS (SELECT 1 FROM user_games g
+                     WHERE g.user_id = u.id AND g.game_name = ?
+                       AND (? IS NULL OR g.game_user_id = ?))
+             AND (
+               COALESCE(p.games_visibility, ?) = 'everyone'
+               OR (COALESCE(p.games_visibility, ?) IN ('friends', 'friends_and_clan')
+                   AND EXISTS (SELECT 1 FROM user_connections f
+                               WHERE f.user_id = ? AND f.connected_user_id = u.id
+                                 AND f.connection_type = 'friend'))
+               OR (COALESCE(p.games_visibility, ?) IN ('clan', 'friends_and_clan')
+                   AND EXISTS (SELECT 1 FROM clan_members a
+                               JOIN clan_members b ON b.clan_id = a.clan_id
+                               JOIN clans c ON c.id = a.clan_id AND c.is_active = true
+                               WHERE a.user_id = u.id AND b.user_id = ?))
+             )
+           ))
+           AND {}
+         ORDER BY u.username = ? DESC, u.username LIKE ? DESC, u.username LIKE ? DESC, u.username
+         LIMIT ? OFFSET ?",
+        blocks::not_blocked_sql("u.id")
+    );
+
+    let mut results: Vec<UserSummary> = sqlx::query_as(&sql)
+        .bind(searcher_id)
+        .bind(q)
+        .bind(&contains)
+        .bind(q)
+        .bind(game)
+        .bind(game)
+        .bind(game_user_id)
+        .bind(game_user_id)
+        .bind(&default_games_visibility)
+        .bind(&default_games_visibility)
+        .bind(searcher_id)
+        .bind(&default_games_visibility)
+        .bind(searcher_id)
+        .bind(searcher_id)
+        .bind(searcher_id)
+        .bind(q)
+        .bind(&prefix)
+        .bind(&contains)
+        .bind(bounds.limit)
+        .bind(bounds.offset)
+        .fetch_all(db)
+        .await?;
+
+    let has_more = pagination::take_page(&mut results, bounds);
+
+    Ok(UserSearchResponse {
+        results,
+        page: bounds.page,
+        per_page: bounds.per_page,
+        has_more,
+    })
+}
+
+/// Escape `LIKE` wildcards so user input only matches literally
+pub(crate) fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_VISIBILITIES: [ProfileVisibility; 5] = [
+        ProfileVisibility::Everyone,
+        ProfileVisibility::Friends,
+        ProfileVisibility::Clan,
+        ProfileVisibility::FriendsAndClan,
+        ProfileVisibility::Private,
+    ];
+
+    fn visible_to(relationship: Relationship) -> Vec<ProfileVisibility> {
+        ALL_VISIBILITIES
+            .into_iter()
+            .filter(|visibility| relationship.can_see(*visibility))
+            .collect()
+    }
+
+    #[test]
+    fn owner_sees_everything() {
+        let owner = Relationship {
+            is_self: true,
+            ..Relationship::default()
+        };
+
+        assert_eq!(visible_to(owner), ALL_VISIBILITIES);
+    }
+
+    #[test]
+    fn strangers_only_see_public_sections() {
+        assert_eq!(
+            visible_to(Relationship::default()),
+            [ProfileVisibility::Everyone]
+        );
+    }
+
+    #[test]
+    fn friends_see_friend_sections() {
+        let friend = Relationship {
+            friend: true,
+            ..Relationship::default()
+        };
+
+        assert_eq!(
+            visible_to(friend),
+            [
+                ProfileVisibility::Everyone,
+                ProfileVisibility::Friends,
+                ProfileVisibility::FriendsAndClan,
+            ]
+        );
+    }
+
+    #[test]
+    fn clanmates_see_clan_sections() {
+        let clanmate = Relationship {
+            clanmate: true,
+            ..Relationship::default()
+        };
+
+        assert_eq!(
+            visible_to(clanmate),
+            [
+                ProfileVisibility::Everyone,
+                ProfileVisibility::Clan,
+                ProfileVisibility::FriendsAndClan,
+            ]
+        );
+    }
+
+    #[test]
+    fn private_sections_are_hidden_from_friendly_clanmates() {
+        let both = Relationship {
+            is_self: false,
+            friend: true,
+            clanmate: true,
+        };
+
+        assert!(!both.can_see(ProfileVisibility::Private));
+        assert_eq!(visible_to(both).len(), 4);
+    }
+
+    #[test]
+    fn escape_like_escapes_wildcards() {
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("snake_case"), "snake\\_case");
+        assert_eq!(escape_like("%_%"), "\\%\\_\\%");
+    }
+
+    #[test]
+    fn escape_like_escapes_backslashes_first() {
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+        assert_eq!(escape_like("\\%"), "\\\\\\%");
+    }
+
+    #[test]
+    fn escape_like_leaves_plain_text_alone() {
+        assert_eq!(escape_like("PlayerOne"), "PlayerOne");
+        assert_eq!(escape_like("ünïcødé 名前"), "ünïcødé 名前");
+        assert_eq!(escape_like(""), "");
+    }
+}
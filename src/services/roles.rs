@@ -0,0 +1,141 @@
+use sqlx::MySqlPool;
+use std::collections::HashSet;
+
+use crate::{
+    models::{Role, RoleWithPermissions},
+    utils::{errors::Result, AppError},
+};
+
+/// Names of the roles currently granted to a user, embedded in their access tokens
+pub async fn user_roles(db: &MySqlPool, user_id: &str) -> Result<Vec<String>> {
+    let roles: Vec<(String,)> = sqlx::query_as(
+        "SELECT r.name FROM user_roles ur
+         JOIN roles r ON r.id = ur.role_id
+         WHERE ur.user_id = ?
+         ORDER BY r.name",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(roles.into_iter().map(|(name,)| name).collect())
+}
+
+/// Permissions granted by the roles in a token that the user still holds.
+/// Roles removed since the token was issued grant nothing.
+pub async fn permissions_for(
+    db: &MySqlPool,
+    user_id: &str,
+    roles: &[String],
+) -> Result<HashSet<String>> {
+    if roles.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let placeholders = vec!["?"; roles.len()].join(", ");
+    let sql = format!(
+        "SELECT DISTINCT p.name FROM user_roles ur
+         JOIN roles r ON r.id = ur.role_id
+         JOIN role_permissions rp ON rp.role_id = r.id
+         JOIN permissions p ON p.id = rp.permission_id
+         WHERE ur.user_id = ? AND r.name IN ({})",
+        placeholders
+    );
+
+    let mut query = sqlx::query_as::<_, (String,)>(&sql).bind(user_id);
+    for role in roles {
+        query = query.bind(role);
+    }
+
+    let permissions = query.fetch_all(db).await?;
+
+    Ok(permissions.into_iter().map(|(name,)| name).collect())
+}
+
+pub async fn list_roles(db: &MySqlPool) -> Result<Vec<RoleWithPermissions>> {
+    let roles: Vec<Role> = sqlx::query_as("SELECT * FROM roles ORDER BY name")
+        .fetch_all(db)
+        .await?;
+
+    let grants: Vec<(i32, String)> = sqlx::query_as(
+        "SELECT rp.role_id, p.name FROM role_permissions rp
+         JOIN permissions p ON p.id = rp.permission_id
+         ORDER BY p.name",
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(roles
+        .into_iter()
+        .map(|role| {
+            let permissions = grants
+                .iter()
+                .filter(|(role_id, _)| *role_id == role.id)
+                .map(|(_, name)| name.clone())
+                .collect();
+
+            RoleWithPermissions {
+                name: role.name,
+                description: role.description,
+                permissions,
+            }
+        })
+        .collect())
+}
+
+/// Grant `role` to a user. Granting a role the user already has is a no-op.
+pub async fn assign_role(
+    db: &MySqlPool,
+    user_id: &str,
+    role: &str,
+    granted_by: Option<&str>,
+) -> Result<()> {
+    let role_id = find_role_id(db, role).await?;
+
+    let user: Option<(String,)> = sqlx::query_as("SELECT id FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+    if user.is_none() {
+        return Err(AppError::not_found("User"));
+    }
+
+    sqlx::query("INSERT IGNORE INTO user_roles (user_id, role_id, granted_by) VALUES (?, ?, ?)")
+        .bind(user_id)
+        .bind(role_id)
+        .bind(granted_by)
+        .execute(db)
+        .await?;
+
+    tracing::info!(user_id = %user_id, role = %role, granted_by = ?granted_by, "Role granted");
+
+    Ok(())
+}
+
+/// Remove `role` from a user, returning whether they had it
+pub async fn remove_role(db: &MySqlPool, user_id: &str, role: &str) -> Result<bool> {
+    let role_id = find_role_id(db, role).await?;
+
+    let result = sqlx::query("DELETE FROM user_roles WHERE user_id = ? AND role_id = ?")
+        .bind(user_id)
+        .bind(role_id)
+        .execute(db)
+        .await?;
+
+    if result.rows_affected() > 0 {
+        tracing::info!(user_id = %user_id, role = %role, "Role removed");
+    }
+
+    Ok(result.rows_affected() > 0)
+}
+
+async fn find_role_id(db: &MySqlPool, role: &str) -> Result<i32> {
+    let role_id: Option<(i32,)> = sqlx::query_as("SELECT id FROM roles WHERE name = ?")
+        .bind(role)
+        .fetch_optional(db)
+        .await?;
+
+    role_id
+        .map(|(id,)| id)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown role '{}'", role)))
+}
@@ -0,0 +1,229 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::MySqlPool;
+
+use crate::{
+    models::UserSession,
+    utils::{self, errors::Result, AppError},
+};
+
+/// Create a new session and return it along with the plaintext refresh token
+pub async fn create_session(
+    db: &MySqlPool,
+    user_id: &str,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    ttl: Duration,
+) -> Result<(UserSession, String)> {
+    let refresh_token = utils::generate_session_token();
+    let session = UserSession::new(
+        user_id.to_string(),
+        utils::hash_token(&refresh_token),
+        Utc::now() + ttl,
+        user_agent,
+        ip_address,
+    );
+
+    sqlx::query(
+        "INSERT INTO user_sessions (id, user_id, token_hash, expires_at, user_agent, ip_address)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&session.id)
+    .bind(&session.user_id)
+    .bind(&session.token_hash)
+    .bind(session.expires_at)
+    .bind(&session.user_agent)
+    .bind(&session.ip_address)
+    .execute(db)
+    .await?;
+
+    Ok((session, refresh_token))
+}
+
+/// Exchange a refresh token for a new one, keeping the same session.
+///
+/// Every rotated token is kept in `session_refresh_tokens`. Presenting any of
+/// them again means it was copied, so the whole session is revoked and both
+/// parties have to log in again.
+pub async fn rotate_refresh_token(
+    db: &MySqlPool,
+    refresh_token: &str,
+    ttl: Duration,
+) -> Result<(UserSession, String)> {
+    let token_hash = utils::hash_token(refresh_token);
+
+    let session: Option<UserSession> =
+        sqlx::query_as("SELECT * FROM user_sessions WHERE token_hash = ?")
+            .bind(&token_hash)
+            .fetch_optional(db)
+            .await?;
+
+    let session = match session {
+        Some(session) => session,
+        None => {
+            detect_reuse(db, &token_hash).await?;
+            return Err(AppError::Authentication(
+                "Invalid refresh token".to_string(),
+            ));
+        }
+    };
+
+    if !session.is_active() {
+        return Err(AppError::Authentication(
+            "Session has expired or been revoked".to_string(),
+        ));
+    }
+
+    let new_token = utils::generate_session_token();
+    let new_hash = utils::hash_token(&new_token);
+    let expires_at = Utc::now() + ttl;
+
+    let mut tx = db.begin().await?;
+
+    // Guard on the current hash so two concurrent refreshes can't both succeed
+    let result = sqlx::query(
+        "UPDATE user_sessions
+         SET token_hash = ?, expires_at = ?, last_used = NOW()
+         WHERE id = ? AND token_hash = ? AND revoked_at IS NULL",
+    )
+    .bind(&new_hash)
+    .bind(expires_at)
+    .bind(&session.id)
+    .bind(&token_hash)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        tx.rollback().await?;
+        detect_reuse(db, &token_hash).await?;
+        return Err(AppError::Authentication(
+            "Invalid refresh token".to_string(),
+        ));
+    }
+
+    sqlx::query("INSERT INTO session_refresh_tokens (token_hash, session_id) VALUES (?, ?)")
+        .bind(&token_hash)
+        .bind(&session.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    let session = UserSession {
+        token_hash: new_hash,
+        expires_at,
+        ..session
+    };
+
+    Ok((session, new_token))
+}
+
+/// Revoke the session a rotated-out refresh token belonged to, however far back
+/// in the session's lineage it is
+async fn detect_reuse(db: &MySqlPool, token_hash: &str) -> Result<()> {
+    let reused: Option<(String, String, Option<DateTime<Utc>>)> = sqlx::query_as(
+        "SELECT s.id, s.user_id, s.revoked_at FROM session_refresh_tokens t
+         JOIN user_sessions s ON s.id = t.session_id
+         WHERE t.token_hash = ?",
+    )
+    .bind(token_hash)
+    .fetch_optional(db)
+    .await?;
+
+    if let Some((session_id, user_id, revoked_at)) = reused {
+        if revoked_at.is_none() {
+            tracing::warn!(
+                session_id = %session_id,
+                user_id = %user_id,
+                "Refresh token reuse detected, revoking session"
+            );
+            revoke_session(db, &session_id).await?;
+        }
+
+        return Err(AppError::Authentication(
+            "Refresh token reuse detected, session revoked".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether the session exists for the user and has not expired or been revoked
+pub async fn is_session_active(db: &MySqlPool, session_id: &str, user_id: &str) -> Result<bool> {
+    let count: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM user_sessions
+         WHERE id = ? AND user_id = ? AND revoked_at IS NULL AND expires_at > NOW()",
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(count.0 > 0)
+}
+
+pub async fn revoke_session(db: &MySqlPool, session_id: &str) -> Result<()> {
+    sqlx::query("UPDATE user_sessions SET revoked_at = NOW() WHERE id = ? AND revoked_at IS NULL")
+        .bind(session_id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Active sessions for a user, most recently used first
+pub async fn list_active_sessions(db: &MySqlPool, user_id: &str) -> Result<Vec<UserSession>> {
+    let sessions = sqlx::query_as(
+        "SELECT * FROM user_sessions
+         WHERE user_id = ? AND revoked_at IS NULL AND expires_at > NOW()
+         ORDER BY last_used DESC",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Revoke one of the user's own sessions, returning false if it doesn't belong to them
+pub async fn revoke_user_session(db: &MySqlPool, user_id: &str, session_id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE user_sessions SET revoked_at = NOW()
+         WHERE id = ? AND user_id = ? AND revoked_at IS NULL",
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke every session of the user except `keep_session_id`
+pub async fn revoke_other_sessions(
+    db: &MySqlPool,
+    user_id: &str,
+    keep_session_id: &str,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE user_sessions SET revoked_at = NOW()
+         WHERE user_id = ? AND id <> ? AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .bind(keep_session_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Revoke every session of the user, e.g. after a password reset
+pub async fn revoke_all_sessions(db: &MySqlPool, user_id: &str) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE user_sessions SET revoked_at = NOW() WHERE user_id = ? AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
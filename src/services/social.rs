@@ -0,0 +1,259 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{FromRow, MySqlPool};
+use uuid::Uuid;
+
+use crate::{
+    models::{Friend, FriendRequest, FriendRequestInbox},
+    services::blocks::{self, Interaction},
+    utils::{errors::Result, AppError},
+};
+
+/// Friends seen within this many minutes are shown as online
+const ONLINE_WINDOW_MINUTES: i64 = 5;
+
+#[derive(FromRow)]
+struct FriendRow {
+    user_id: String,
+    username: String,
+    avatar_url: Option<String>,
+    friends_since: DateTime<Utc>,
+    last_seen_at: Option<DateTime<Utc>>,
+}
+
+/// Record that the user is active, writing at most once a minute
+pub async fn touch_last_seen(db: &MySqlPool, user_id: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE users SET last_seen_at = NOW()
+         WHERE id = ? AND (last_seen_at IS NULL OR last_seen_at < NOW() - INTERVAL 1 MINUTE)",
+    )
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Friendships are stored as a `friend` connection in each direction
+pub async fn are_friends(db: &MySqlPool, user_id: &str, other_id: &str) -> Result<bool> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM user_connections
+         WHERE user_id = ? AND connected_user_id = ? AND connection_type = 'friend'",
+    )
+    .bind(user_id)
+    .bind(other_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(count > 0)
+}
+
+pub async fn list_friends(db: &MySqlPool, user_id: &str) -> Result<Vec<Friend>> {
+    let rows: Vec<FriendRow> = sqlx::query_as(
+        "SELECT u.id AS user_id, u.username, u.avatar_url, c.created_at AS friends_since,
+                u.last_seen_at
+         FROM user_connections c
+         JOIN users u ON u.id = c.connected_user_id
+         WHERE c.user_id = ? AND c.connection_type = 'friend'
+           AND u.is_active = true AND u.deleted_at IS NULL
+         ORDER BY u.last_seen_at IS NULL, u.last_seen_at DESC, u.username",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let online_since = Utc::now() - Duration::minutes(ONLINE_WINDOW_MINUTES);
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Friend {
+            online: row.last_seen_at.is_some_and(|seen| seen > online_since),
+            user_id: row.user_id,
+            username: row.username,
+            avatar_url: row.avatar_url,
+            friends_since: row.friends_since,
+            last_seen_at: row.last_seen_at,
+        })
+        .collect())
+}
+
+pub async fn send_friend_request(
+    db: &MySqlPool,
+    sender_id: &str,
+    recipient_id: &str,
+) -> Result<FriendRequest> {
+    if sender_id == recipient_id {
+        return Err(AppError::bad_request(
+            "You can't send a friend request to yourself",
+        ));
+    }
+
+    let recipient: Option<(String,)> = sqlx::query_as(
+        "SELECT id FROM users WHERE id = ? AND is_active = true AND deleted_at IS NULL",
+    )
+    .bind(recipient_id)
+    .fetch_optional(db)
+    .await?;
+    if recipient.is_none() {
+        return Err(AppError::not_found("User"));
+    }
+
+    blocks::ensure_can_interact(db, sender_id, recipient_id, Interaction::FriendRequest).await?;
+
+    if are_friends(db, sender_id, recipient_id).await? {
+        return Err(AppError::Conflict("You are already friends".to_string()));
+    }
+
+    let reverse: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM friend_requests WHERE sender_id = ? AND recipient_id = ?")
+            .bind(recipient_id)
+            .bind(sender_id)
+            .fetch_optional(db)
+            .await?;
+    if reverse.is_some() {
+        return Err(AppError::Conflict(
+            "This user has already sent you a friend request; accept it instead".to_string(),
+        ));
+    }
+
+    let request = FriendRequest {
+        id: Uuid::new_v4().to_string(),
+        sender_id: sender_id.to_string(),
+        recipient_id: recipient_id.to_string(),
+        created_at: Utc::now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO friend_requests (id, sender_id, recipient_id, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(&request.id)
+    .bind(&request.sender_id)
+    .bind(&request.recipient_id)
+    .bind(request.created_at)
+    .execute(db)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::Conflict("Friend request already sent".to_string())
+        }
+        e => AppError::Database(e),
+    })?;
+
+    Ok(request)
+}
+
+/// Pending requests sent to and by the user, newest first
+pub async fn friend_requests(db: &MySqlPool, user_id: &str) -> Result<FriendRequestInbox> {
+    let incoming = sqlx::query_as(
+        "SELECT r.id, u.id AS user_id, u.username, u.avatar_url, r.created_at
+         FROM friend_requests r
+         JOIN users u ON u.id = r.sender_id
+         WHERE r.recipient_id = ? AND u.is_active = true AND u.deleted_at IS NULL
+         ORDER BY r.created_at DESC",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    let outgoing = sqlx::query_as(
+        "SELECT r.id, u.id AS user_id, u.username, u.avatar_url, r.created_at
+         FROM friend_requests r
+         JOIN users u ON u.id = r.recipient_id
+         WHERE r.sender_id = ? AND u.is_active = true AND u.deleted_at IS NULL
+         ORDER BY r.created_at DESC",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(FriendRequestInbox { incoming, outgoing })
+}
+
+/// Accept a request addressed to the user, making the two users friends
+pub async fn accept_friend_request(db: &MySqlPool, user_id: &str, request_id: &str) -> Result<()> {
+    let request = find_request(db, request_id)
+        .await?
+        .filter(|request| request.recipient_id == user_id)
+        .ok_or_else(|| AppError::not_found("Friend request"))?;
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM friend_requests
+         WHERE (sender_id = ? AND recipient_id = ?) OR (sender_id = ? AND recipient_id = ?)",
+    )
+    .bind(&request.sender_id)
+    .bind(&request.recipient_id)
+    .bind(&request.recipient_id)
+    .bind(&request.sender_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT IGNORE INTO user_connections (user_id, connected_user_id, connection_type)
+         VALUES (?, ?, 'friend'), (?, ?, 'friend')",
+    )
+    .bind(&request.sender_id)
+    .bind(&request.recipient_id)
+    .bind(&request.recipient_id)
+    .bind(&request.sender_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Turn down a request addressed to the user
+pub async fn decline_friend_request(db: &MySqlPool, user_id: &str, request_id: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM friend_requests WHERE id = ? AND recipient_id = ?")
+        .bind(request_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("Friend request"));
+    }
+
+    Ok(())
+}
+
+/// Withdraw a request the user sent
+pub async fn cancel_friend_request(db: &MySqlPool, user_id: &str, request_id: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM friend_requests WHERE id = ? AND sender_id = ?")
+        .bind(request_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("Friend request"));
+    }
+
+    Ok(())
+}
+
+/// End a friendship for both sides. Returns false if they weren't friends.
+pub async fn remove_friend(db: &MySqlPool, user_id: &str, friend_id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "DELETE FROM user_connections
+         WHERE connection_type = 'friend'
+           AND ((user_id = ? AND connected_user_id = ?) OR (user_id = ? AND connected_user_id = ?))",
+    )
+    .bind(user_id)
+    .bind(friend_id)
+    .bind(friend_id)
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+async fn find_request(db: &MySqlPool, request_id: &str) -> Result<Option<FriendRequest>> {
+    Ok(sqlx::query_as("SELECT * FROM friend_requests WHERE id = ?")
+        .bind(request_id)
+        .fetch_optional(db)
+        .await?)
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::MySqlPool;
+use uuid::Uuid;
+
+use crate::utils::{self, errors::Result, AppError};
+
+/// What a single-use token may be redeemed for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenPurpose {
+    PasswordReset,
+    EmailVerification,
+}
+
+impl std::fmt::Display for TokenPurpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            TokenPurpose::PasswordReset => "password_reset",
+            TokenPurpose::EmailVerification => "email_verification",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+/// Issue a new token, invalidating any unused token for the same purpose
+pub async fn issue(
+    db: &MySqlPool,
+    user_id: &str,
+    purpose: TokenPurpose,
+    ttl: Duration,
+) -> Result<String> {
+    let token = utils::generate_session_token();
+
+    sqlx::query(
+        "UPDATE user_tokens SET used_at = NOW()
+         WHERE user_id = ? AND purpose = ? AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .bind(purpose.to_string())
+    .execute(db)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO user_tokens (id, user_id, purpose, token_hash, expires_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(user_id)
+    .bind(purpose.to_string())
+    .bind(utils::hash_token(&token))
+    .bind(Utc::now() + ttl)
+    .execute(db)
+    .await?;
+
+    Ok(token)
+}
+
+/// When the most recent token for this purpose was issued, for resend throttling
+pub async fn last_issued_at(
+    db: &MySqlPool,
+    user_id: &str,
+    purpose: TokenPurpose,
+) -> Result<Option<DateTime<Utc>>> {
+    let last: (Option<DateTime<Utc>>,) =
+        sqlx::query_as("SELECT MAX(created_at) FROM user_tokens WHERE user_id = ? AND purpose = ?")
+            .bind(user_id)
+            .bind(purpose.to_string())
+            .fetch_one(db)
+            .await?;
+
+    Ok(last.0)
+}
+
+/// Redeem a token, returning the user it was issued to. Each token works once.
+pub async fn consume(db: &MySqlPool, purpose: TokenPurpose, token: &str) -> Result<String> {
+    let token_hash = utils::hash_token(token);
+
+    let result = sqlx::query(
+        "UPDATE user_tokens SET used_at = NOW()
+         WHERE token_hash = ? AND purpose = ? AND used_at IS NULL AND expires_at > NOW()",
+    )
+    .bind(&token_hash)
+    .bind(purpose.to_string())
+    .execute(db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::bad_request("Invalid or expired token"));
+    }
+
+    let (user_id,): (String,) =
+        sqlx::query_as("SELECT user_id FROM user_tokens WHERE token_hash = ?")
+            .bind(&token_hash)
+            .fetch_one(db)
+            .await?;
+
+    Ok(user_id)
+}
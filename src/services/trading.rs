@@ -0,0 +1,423 @@
+use chrono::Utc;
+use sqlx::{MySql, MySqlPool, Transaction};
+
+use crate::{
+    models::{
+        CreateTradeRequest, Trade, TradeItem, TradePolicy, TradeSettings, TradeStatus,
+        TrustedTrader, UserInventory,
+    },
+    services::{
+        blocks::{self, Interaction},
+        profiles, social,
+    },
+    utils::{errors::Result, AppError},
+};
+
+/// Most items one side can put into a trade
+const MAX_TRADE_ITEMS: usize = 20;
+
+pub async fn trade_settings(db: &MySqlPool, user_id: &str) -> Result<TradeSettings> {
+    let row: Option<(String, bool)> = sqlx::query_as(
+        "SELECT inbound_policy, auto_accept_trusted_gifts FROM user_trade_settings WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(match row {
+        Some((inbound_policy, auto_accept_trusted_gifts)) => TradeSettings {
+            inbound_policy: inbound_policy.into(),
+            auto_accept_trusted_gifts,
+        },
+        None => TradeSettings::default(),
+    })
+}
+
+pub async fn update_trade_settings(
+    db: &MySqlPool,
+    user_id: &str,
+    settings: &TradeSettings,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO user_trade_settings (user_id, inbound_policy, auto_accept_trusted_gifts)
+         VALUES (?, ?, ?)
+         ON DUPLICATE KEY UPDATE inbound_policy = VALUES(inbound_policy),
+         auto_accept_trusted_gifts = VALUES(auto_accept_trusted_gifts)",
+    )
+    .bind(user_id)
+    .bind(settings.inbound_policy.to_string())
+    .bind(settings.auto_accept_trusted_gifts)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Whether `user_id` has marked `trader_id` as a trusted trader
+pub async fn is_trusted_trader(db: &MySqlPool, user_id: &str, trader_id: &str) -> Result<bool> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM user_connections
+         WHERE user_id = ? AND connected_user_id = ? AND connection_type = 'trader'",
+    )
+    .bind(user_id)
+    .bind(trader_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(count > 0)
+}
+
+pub async fn list_trusted_traders(db: &MySqlPool, user_id: &str) -> Result<Vec<TrustedTrader>> {
+    Ok(sqlx::query_as(
+        "SELECT u.id AS user_id, u.username, u.avatar_url, c.created_at AS trusted_since
+         FROM user_connections c
+         JOIN users u ON u.id = c.connected_user_id
+         WHERE c.user_id = ? AND c.connection_type = 'trader'
+           AND u.is_active = true AND u.deleted_at IS NULL
+         ORDER BY u.username",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?)
+}
+
+/// Mark a user as a trusted trader. Trusting someone already trusted is a no-op.
+pub async fn trust_trader(db: &MySqlPool, user_id: &str, trader_id: &str) -> Result<()> {
+    if user_id == trader_id {
+        return Err(AppError::bad_request(
+            "You can't trust yourself as a trader",
+        ));
+    }
+
+    let trader: Option<(String,)> = sqlx::query_as(
+        "SELECT id FROM users WHERE id = ? AND is_active = true AND deleted_at IS NULL",
+    )
+    .bind(trader_id)
+    .fetch_optional(db)
+    .await?;
+    if trader.is_none() {
+        return Err(AppError::not_found("User"));
+    }
+
+    blocks::ensure_can_interact(db, user_id, trader_id, Interaction::Trade).await?;
+
+    sqlx::query(
+        "INSERT IGNORE INTO user_connections (user_id, connected_user_id, connection_type)
+         VALUES (?, ?, 'trader')",
+    )
+    .bind(user_id)
+    .bind(trader_id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns false if the user wasn't a trusted trader
+pub async fn untrust_trader(db: &MySqlPool, user_id: &str, trader_id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "DELETE FROM user_connections
+         WHERE user_id = ? AND connected_user_id = ? AND connection_type = 'trader'",
+    )
+    .bind(user_id)
+    .bind(trader_id)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Check the recipient's inbound trade policy against the initiator
+async fn ensure_trade_allowed(
+    db: &MySqlPool,
+    initiator_id: &str,
+    recipient_id: &str,
+    policy: TradePolicy,
+) -> Result<()> {
+    let (allowed, audience) = match policy {
+        TradePolicy::Anyone => return Ok(()),
+        TradePolicy::Friends => (
+            social::are_friends(db, recipient_id, initiator_id).await?,
+            "friends",
+        ),
+        TradePolicy::TrustedTraders => (
+            is_trusted_trader(db, recipient_id, initiator_id).await?,
+            "trusted traders",
+        ),
+        TradePolicy::Clanmates => (
+            profiles::are_clanmates(db, recipient_id, initiator_id).await?,
+            "clanmates",
+        ),
+        TradePolicy::Nobody => {
+            return Err(AppError::forbidden("This user isn't accepting trades"));
+        }
+    };
+
+    if !allowed {
+        return Err(AppError::Forbidden(format!(
+            "This user only accepts trades from {}",
+            audience
+        )));
+    }
+
+    Ok(())
+}
+
+/// Offer items to another player. The items must belong to the initiator, be
+/// tradeable and not already be on offer in another open trade. The recipient's
+/// inbound policy decides who may propose; gifts from traders they trust are
+/// accepted and settled straight away when they have opted in.
+pub async fn propose_trade(
+    db: &MySqlPool,
+    initiator_id: &str,
+    request: &CreateTradeRequest,
+) -> Result<Trade> {
+    if request.recipient_id == initiator_id {
+        return Err(AppError::bad_request("You can't trade with yourself"));
+    }
+
+    let mut offered_items = request.offered_items.clone();
+    offered_items.sort();
+    offered_items.dedup();
+    if offered_items.is_empty() {
+        return Err(AppError::bad_request("Offer at least one item"));
+    }
+    if offered_items.len() > MAX_TRADE_ITEMS {
+        return Err(AppError::Validation(format!(
+            "A trade can include at most {} items",
+            MAX_TRADE_ITEMS
+        )));
+    }
+
+    let recipient: Option<(String,)> = sqlx::query_as(
+        "SELECT id FROM users WHERE id = ? AND is_active = true AND deleted_at IS NULL",
+    )
+    .bind(&request.recipient_id)
+    .fetch_optional(db)
+    .await?;
+    if recipient.is_none() {
+        return Err(AppError::not_found("User"));
+    }
+
+    blocks::ensure_can_interact(db, initiator_id, &request.recipient_id, Interaction::Trade)
+        .await?;
+
+    let settings = trade_settings(db, &request.recipient_id).await?;
+    ensure_trade_allowed(
+        db,
+        initiator_id,
+        &request.recipient_id,
+        settings.inbound_policy,
+    )
+    .await?;
+
+    let mut trade = Trade::new(initiator_id.to_string(), request.recipient_id.clone());
+
+    // Only the initiator offers items in a proposal, so every proposal is a gift
+    let auto_accept = settings.auto_accept_trusted_gifts
+        && is_trusted_trader(db, &request.recipient_id, initiator_id).await?;
+
+    let mut tx = db.begin().await?;
+
+    for user_item_id in &offered_items {
+        let item: UserInventory = sqlx::query_as(
+            "SELECT id, user_id, item_id, acquired_at, is_tradeable, is_equipped, custom_name
+             FROM user_inventory WHERE id = ? AND user_id = ? FOR UPDATE",
+        )
+        .bind(user_item_id)
+        .bind(initiator_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("Item"))?;
+
+        if !item.is_tradeable {
+            return Err(AppError::bad_request(
+                "One of the offered items can't be traded",
+            ));
+        }
+
+        let (open_trades,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM trade_items ti
+             JOIN trades t ON t.id = ti.trade_id
+             WHERE ti.user_item_id = ? AND t.status IN ('pending', 'accepted')",
+        )
+        .bind(user_item_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if open_trades > 0 {
+            return Err(AppError::Conflict(
+                "One of the offered items is already part of another trade".to_string(),
+            ));
+        }
+    }
+
+    sqlx::query(
+        "INSERT INTO trades (id, initiator_id, recipient_id, status, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&trade.id)
+    .bind(&trade.initiator_id)
+    .bind(&trade.recipient_id)
+    .bind(&trade.status)
+    .bind(trade.created_at)
+    .bind(trade.updated_at)
+    .execute(&mut *tx)
+    .await?;
+
+    for user_item_id in &offered_items {
+        sqlx::query(
+            "INSERT INTO trade_items (trade_id, user_item_id, offered_by) VALUES (?, ?, ?)",
+        )
+        .bind(&trade.id)
+        .bind(user_item_id)
+        .bind(initiator_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if auto_accept {
+        settle_trade(&mut tx, &mut trade).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(trade)
+}
+
+/// Accept a pending trade as its recipient; the trade settles straight away
+pub async fn accept_trade(db: &MySqlPool, user_id: &str, trade_id: &str) -> Result<Trade> {
+    let mut tx = db.begin().await?;
+
+    let mut trade = lock_pending_trade(&mut tx, trade_id, Some(user_id)).await?;
+    settle_trade(&mut tx, &mut trade).await?;
+    tx.commit().await?;
+
+    Ok(trade)
+}
+
+/// A trade by ID, whoever its parties are
+pub async fn find_trade(db: &MySqlPool, trade_id: &str) -> Result<Trade> {
+    sqlx::query_as(
+        "SELECT id, initiator_id, recipient_id, status, created_at, updated_at, completed_at
+         FROM trades WHERE id = ?",
+    )
+    .bind(trade_id)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| AppError::not_found("Trade"))
+}
+
+/// Games of the items in a trade; `None` stands for Bridge-wide items
+pub async fn trade_item_games(db: &MySqlPool, trade_id: &str) -> Result<Vec<Option<String>>> {
+    let rows: Vec<(Option<String>,)> = sqlx::query_as(
+        "SELECT DISTINCT g.name FROM trade_items ti
+         JOIN user_inventory ui ON ui.id = ti.user_item_id
+         JOIN inventory_items i ON i.id = ui.item_id
+         LEFT JOIN games g ON g.id = i.game_id
+         WHERE ti.trade_id = ?",
+    )
+    .bind(trade_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|(game,)| game).collect())
+}
+
+/// Settle a pending trade that a game server brokered between its players. The
+/// caller checks that the server may act for both parties and their items.
+pub async fn settle_pending_trade(db: &MySqlPool, trade_id: &str) -> Result<Trade> {
+    let mut tx = db.begin().await?;
+
+    let mut trade = lock_pending_trade(&mut tx, trade_id, None).await?;
+    settle_trade(&mut tx, &mut trade).await?;
+    tx.commit().await?;
+
+    Ok(trade)
+}
+
+/// Lock a trade for settlement, refusing trades that are no longer pending. With
+/// `recipient_id`, trades sent to anyone else are reported as not found.
+async fn lock_pending_trade(
+    tx: &mut Transaction<'_, MySql>,
+    trade_id: &str,
+    recipient_id: Option<&str>,
+) -> Result<Trade> {
+    let trade: Trade = sqlx::query_as(
+        "SELECT id, initiator_id, recipient_id, status, created_at, updated_at, completed_at
+         FROM trades WHERE id = ? FOR UPDATE",
+    )
+    .bind(trade_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    .filter(|trade: &Trade| recipient_id.is_none_or(|id| trade.recipient_id == id))
+    .ok_or_else(|| AppError::not_found("Trade"))?;
+
+    if !trade.can_be_accepted() {
+        return Err(AppError::Conflict(format!(
+            "This trade is already {}",
+            trade.status
+        )));
+    }
+
+    Ok(trade)
+}
+
+/// Hand every item in the trade to the other party and mark the trade completed.
+/// Items must still belong to whoever offered them; their open listings are cancelled.
+async fn settle_trade(tx: &mut Transaction<'_, MySql>, trade: &mut Trade) -> Result<()> {
+    let items: Vec<TradeItem> = sqlx::query_as(
+        "SELECT id, trade_id, user_item_id, offered_by FROM trade_items WHERE trade_id = ?",
+    )
+    .bind(&trade.id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    for item in &items {
+        let receiver = if item.offered_by == trade.initiator_id {
+            &trade.recipient_id
+        } else {
+            &trade.initiator_id
+        };
+
+        let owned: Option<(String,)> =
+            sqlx::query_as("SELECT id FROM user_inventory WHERE id = ? AND user_id = ? FOR UPDATE")
+                .bind(&item.user_item_id)
+                .bind(&item.offered_by)
+                .fetch_optional(&mut **tx)
+                .await?;
+        if owned.is_none() {
+            return Err(AppError::Conflict(
+                "One of the traded items is no longer available".to_string(),
+            ));
+        }
+
+        sqlx::query("UPDATE user_inventory SET user_id = ?, is_equipped = false WHERE id = ?")
+            .bind(receiver)
+            .bind(&item.user_item_id)
+            .execute(&mut **tx)
+            .await?;
+
+        sqlx::query(
+            "UPDATE marketplace_listings SET status = 'cancelled'
+             WHERE user_item_id = ? AND status = 'active'",
+        )
+        .bind(&item.user_item_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    let now = Utc::now();
+    sqlx::query("UPDATE trades SET status = ?, updated_at = ?, completed_at = ? WHERE id = ?")
+        .bind(TradeStatus::Completed.to_string())
+        .bind(now)
+        .bind(now)
+        .bind(&trade.id)
+        .execute(&mut **tx)
+        .await?;
+
+    trade.status = TradeStatus::Completed.to_string();
+    trade.updated_at = now;
+    trade.completed_at = Some(now);
+
+    Ok(())
+}
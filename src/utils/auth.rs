@@ -1,77 +1,111 @@
-use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
-};
 use base64::{engine::general_purpose, Engine as _};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
 
-use crate::utils::errors::{AppError, Result};
+use crate::utils::{
+    errors::{AppError, Result},
+    KeyRing,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // user_id
-    pub exp: i64,    // expiration timestamp
-    pub iat: i64,    // issued at timestamp
+    pub sid: String, // session_id
+    /// Role names at issue time; tokens from before roles existed have none
+    #[serde(default)]
+    pub roles: Vec<String>,
+    pub exp: i64, // expiration timestamp
+    pub iat: i64, // issued at timestamp
 }
 
-pub fn hash_password(password: &str) -> Result<String> {
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-
-    let password_hash = argon2
-        .hash_password(password.as_bytes(), &salt)
-        .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?
-        .to_string();
-
-    Ok(password_hash)
+/// Claims of the token handed out between the password and second-factor steps of a login.
+/// It has no `sid`, so it can never be mistaken for an access token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaChallengeClaims {
+    pub sub: String,     // user_id
+    pub jti: String,     // recorded when the challenge is used, so it works once
+    pub purpose: String, // always MFA_CHALLENGE_PURPOSE
+    pub exp: i64,
+    pub iat: i64,
 }
 
-pub fn verify_password(password: &str, password_hash: &str) -> Result<bool> {
-    let parsed_hash = PasswordHash::new(password_hash)
-        .map_err(|e| AppError::Internal(format!("Invalid password hash: {}", e)))?;
+const MFA_CHALLENGE_PURPOSE: &str = "mfa_challenge";
 
-    let argon2 = Argon2::default();
-    Ok(argon2
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .is_ok())
-}
+/// `typ` header of access tokens (RFC 9068); JWKS consumers must require it
+pub const ACCESS_TOKEN_TYPE: &str = "at+jwt";
+
+/// `typ` header of MFA challenge tokens, which must never be accepted as access tokens
+pub const MFA_CHALLENGE_TYPE: &str = "mfa-challenge+jwt";
 
-pub fn generate_jwt(user_id: &str, secret: &str) -> Result<String> {
+pub async fn generate_jwt(
+    user_id: &str,
+    session_id: &str,
+    roles: Vec<String>,
+    keys: &KeyRing,
+    ttl: Duration,
+) -> Result<String> {
     let now = Utc::now();
-    let expiration = now + Duration::days(7);
+    let expiration = now + ttl;
 
     let claims = Claims {
         sub: user_id.to_string(),
+        sid: session_id.to_string(),
+        roles,
         exp: expiration.timestamp(),
         iat: now.timestamp(),
     };
 
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(|e| AppError::Internal(format!("Failed to generate JWT: {}", e)))?;
+    keys.encode(ACCESS_TOKEN_TYPE, &claims).await
+}
 
-    Ok(token)
+pub async fn validate_jwt(token: &str, keys: &KeyRing) -> Result<Claims> {
+    keys.decode(ACCESS_TOKEN_TYPE, token).await
 }
 
-pub fn validate_jwt(token: &str, secret: &str) -> Result<Claims> {
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
-    )
-    .map_err(|e| AppError::Authentication(format!("Invalid token: {}", e)))?;
+pub async fn generate_mfa_challenge(
+    user_id: &str,
+    keys: &KeyRing,
+    ttl: Duration,
+) -> Result<String> {
+    let now = Utc::now();
 
-    Ok(token_data.claims)
+    let claims = MfaChallengeClaims {
+        sub: user_id.to_string(),
+        jti: Uuid::new_v4().to_string(),
+        purpose: MFA_CHALLENGE_PURPOSE.to_string(),
+        exp: (now + ttl).timestamp(),
+        iat: now.timestamp(),
+    };
+
+    keys.encode(MFA_CHALLENGE_TYPE, &claims).await
 }
 
+pub async fn validate_mfa_challenge(token: &str, keys: &KeyRing) -> Result<MfaChallengeClaims> {
+    let claims: MfaChallengeClaims = keys
+        .decode(MFA_CHALLENGE_TYPE, token)
+        .await
+        .map_err(|_| AppError::Authentication("Invalid MFA challenge".to_string()))?;
+
+    if claims.purpose != MFA_CHALLENGE_PURPOSE {
+        return Err(AppError::Authentication(
+            "Invalid MFA challenge".to_string(),
+        ));
+    }
+
+    Ok(claims)
+}
+
+/// Random URL-safe token used for refresh tokens, OAuth state and similar secrets
 pub fn generate_session_token() -> String {
     use rand::Rng;
     let mut rng = rand::thread_rng();
     let bytes: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-    general_purpose::STANDARD.encode(&bytes)
+    general_purpose::URL_SAFE_NO_PAD.encode(&bytes)
+}
+
+/// Hash an opaque token (refresh token, etc.) for storage; only the hash is persisted
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
 }
@@ -1,9 +1,16 @@
 pub mod auth;
 pub mod errors;
 pub mod logging;
+pub mod nonce_cache;
+pub mod pagination;
+pub mod passwords;
 pub mod secrets;
+pub mod signing_keys;
 
 pub use auth::*;
 pub use errors::*;
 pub use logging::*;
+pub use nonce_cache::NonceCache;
+pub use passwords::PasswordHasher;
 pub use secrets::*;
+pub use signing_keys::KeyRing;
@@ -0,0 +1,98 @@
+use chrono::Utc;
+use std::{collections::HashMap, sync::Mutex};
+
+/// How often expired nonces are swept out
+const PRUNE_INTERVAL_SECONDS: i64 = 60;
+
+/// Remembers request nonces until they expire so a signed request can't be replayed.
+///
+/// Kept in memory: when running several instances, route each game client to
+/// the same instance or keep the clock skew window short.
+#[derive(Debug, Default)]
+pub struct NonceCache {
+    inner: Mutex<NonceCacheInner>,
+}
+
+#[derive(Debug, Default)]
+struct NonceCacheInner {
+    /// Nonce to the unix timestamp after which it can be forgotten
+    seen: HashMap<String, i64>,
+    last_pruned: i64,
+}
+
+impl NonceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `nonce` until `expires_at` (unix seconds). Returns false if it has
+    /// already been used.
+    pub fn insert(&self, nonce: String, expires_at: i64) -> bool {
+        let now = Utc::now().timestamp();
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+
+        if now - inner.last_pruned >= PRUNE_INTERVAL_SECONDS {
+            inner.seen.retain(|_, expires_at| *expires_at >= now);
+            inner.last_pruned = now;
+        }
+
+        match inner.seen.get(&nonce) {
+            Some(existing) if *existing >= now => false,
+            _ => {
+                inner.seen.insert(nonce, expires_at);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_five_minutes() -> i64 {
+        Utc::now().timestamp() + 300
+    }
+
+    #[test]
+    fn rejects_a_nonce_seen_before() {
+        let cache = NonceCache::new();
+
+        assert!(cache.insert("client:abc".to_string(), in_five_minutes()));
+        assert!(!cache.insert("client:abc".to_string(), in_five_minutes()));
+    }
+
+    #[test]
+    fn nonces_are_independent() {
+        let cache = NonceCache::new();
+
+        assert!(cache.insert("client:abc".to_string(), in_five_minutes()));
+        assert!(cache.insert("client:def".to_string(), in_five_minutes()));
+        assert!(cache.insert("other:abc".to_string(), in_five_minutes()));
+    }
+
+    #[test]
+    fn expired_nonces_are_forgotten() {
+        let cache = NonceCache::new();
+        let expired = Utc::now().timestamp() - 1;
+
+        assert!(cache.insert("client:abc".to_string(), expired));
+        assert!(cache.insert("client:abc".to_string(), in_five_minutes()));
+        assert!(!cache.insert("client:abc".to_string(), in_five_minutes()));
+    }
+
+    #[test]
+    fn pruning_drops_expired_entries() {
+        let cache = NonceCache::new();
+        let expired = Utc::now().timestamp() - 1;
+
+        cache.insert("client:old".to_string(), expired);
+        // The first insert prunes before recording, so force another sweep
+        cache.inner.lock().unwrap().last_pruned = 0;
+        cache.insert("client:new".to_string(), in_five_minutes());
+
+        let inner = cache.inner.lock().unwrap();
+        assert!(!inner.seen.contains_key("client:old"));
+        assert!(inner.seen.contains_key("client:new"));
+    }
+}
@@ -0,0 +1,84 @@
+/// Page parameters normalised for a `LIMIT ? OFFSET ?` query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageBounds {
+    /// Page number, starting at 1
+    pub page: i64,
+    pub per_page: i64,
+    /// Rows to fetch: one more than the page tells whether there is a next page
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Turn the requested page and page size into query bounds. Missing or too small
+/// values fall back to the first page and `default` rows; pages hold at most
+/// `max` rows.
+pub fn page_bounds(page: Option<i64>, per_page: Option<i64>, default: i64, max: i64) -> PageBounds {
+    let page = page.unwrap_or(1).max(1);
+    let per_page = per_page.unwrap_or(default).clamp(1, max);
+
+    PageBounds {
+        page,
+        per_page,
+        limit: per_page + 1,
+        // Absurd page numbers saturate to an offset past the end, giving an empty page
+        offset: (page - 1).saturating_mul(per_page),
+    }
+}
+
+/// Drop the extra row fetched by `PageBounds::limit`, returning whether there
+/// is a next page
+pub fn take_page<T>(rows: &mut Vec<T>, bounds: PageBounds) -> bool {
+    let has_more = rows.len() as i64 > bounds.per_page;
+    rows.truncate(bounds.per_page as usize);
+
+    has_more
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_values_give_the_first_default_page() {
+        let bounds = page_bounds(None, None, 20, 50);
+
+        assert_eq!(
+            bounds,
+            PageBounds {
+                page: 1,
+                per_page: 20,
+                limit: 21,
+                offset: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        let bounds = page_bounds(Some(-3), Some(500), 20, 50);
+        assert_eq!((bounds.page, bounds.per_page), (1, 50));
+
+        let bounds = page_bounds(Some(3), Some(0), 20, 50);
+        assert_eq!((bounds.per_page, bounds.offset), (1, 2));
+    }
+
+    #[test]
+    fn huge_page_numbers_saturate() {
+        let bounds = page_bounds(Some(i64::MAX), Some(50), 20, 50);
+
+        assert_eq!(bounds.offset, i64::MAX);
+    }
+
+    #[test]
+    fn take_page_drops_the_extra_row() {
+        let bounds = page_bounds(None, Some(2), 20, 50);
+
+        let mut rows = vec![1, 2, 3];
+        assert!(take_page(&mut rows, bounds));
+        assert_eq!(rows, [1, 2]);
+
+        let mut rows = vec![1, 2];
+        assert!(!take_page(&mut rows, bounds));
+        assert_eq!(rows, [1, 2]);
+    }
+}
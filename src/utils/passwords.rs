@@ -0,0 +1,264 @@
+use argon2::{
+    password_hash::{
+        rand_core::OsRng, PasswordHash, PasswordHasher as _, PasswordVerifier as _, SaltString,
+    },
+    Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version,
+};
+use base64::{engine::general_purpose, Engine as _};
+use sha2::{Digest, Sha256};
+use sqlx::MySqlPool;
+
+use crate::{
+    config::PasswordPolicyConfig,
+    utils::{
+        errors::{AppError, Result},
+        generate_session_token, secrets,
+    },
+};
+
+/// `app_secrets.key_name` of the server-side pepper
+const PEPPER_KEY_NAME: &str = "password_pepper";
+
+/// Argon2id hashing with the configured cost parameters and optional pepper.
+///
+/// Peppered hashes carry a short ID of the pepper in the PHC `keyid` field, so hashes
+/// made before the pepper was turned on (or after it was turned off) still verify
+/// and are picked up by [`PasswordHasher::needs_rehash`].
+pub struct PasswordHasher {
+    params: Params,
+    pepper: Option<Pepper>,
+    /// Whether new hashes use the pepper; it is still loaded for verification when off
+    use_pepper: bool,
+    /// Hash of a random password, checked against for unknown accounts
+    dummy_hash: String,
+}
+
+struct Pepper {
+    key: Vec<u8>,
+    id: KeyId,
+}
+
+impl Pepper {
+    fn new(secret: &str) -> Result<Self> {
+        let key = general_purpose::STANDARD
+            .decode(secret)
+            .map_err(|e| AppError::Internal(format!("Invalid password pepper: {}", e)))?;
+        let id = KeyId::new(&Sha256::digest(&key)[..4])
+            .map_err(|e| AppError::Internal(format!("Invalid pepper key ID: {}", e)))?;
+
+        Ok(Self { key, id })
+    }
+}
+
+impl PasswordHasher {
+    pub async fn load(db: &MySqlPool, config: &PasswordPolicyConfig) -> Result<Self> {
+        let pepper = if config.pepper {
+            Some(secrets::get_or_create_secret(db, PEPPER_KEY_NAME).await?)
+        } else {
+            // Keep verifying hashes made while the pepper was on
+            secrets::get_secret(db, PEPPER_KEY_NAME).await?
+        };
+
+        Self::new(config, pepper.as_deref())
+    }
+
+    pub fn new(config: &PasswordPolicyConfig, pepper: Option<&str>) -> Result<Self> {
+        let pepper = pepper.map(Pepper::new).transpose()?;
+        let use_pepper = config.pepper && pepper.is_some();
+
+        let mut builder = ParamsBuilder::new();
+        builder
+            .m_cost(config.memory_kib)
+            .t_cost(config.iterations)
+            .p_cost(config.parallelism);
+        if let Some(pepper) = pepper.as_ref().filter(|_| use_pepper) {
+            builder.keyid(pepper.id);
+        }
+        let params = builder
+            .build()
+            .map_err(|e| AppError::Internal(format!("Invalid Argon2 parameters: {}", e)))?;
+
+        let mut hasher = Self {
+            params,
+            pepper,
+            use_pepper,
+            dummy_hash: String::new(),
+        };
+        hasher.dummy_hash = hasher.hash(&generate_session_token())?;
+
+        Ok(hasher)
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        let pepper = self.pepper.as_ref().filter(|_| self.use_pepper);
+
+        let password_hash = Self::argon2(pepper, self.params.clone())?
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?
+            .to_string();
+
+        Ok(password_hash)
+    }
+
+    pub fn verify(&self, password: &str, password_hash: &str) -> Result<bool> {
+        let parsed_hash = PasswordHash::new(password_hash)
+            .map_err(|e| AppError::Internal(format!("Invalid password hash: {}", e)))?;
+        let params = Params::try_from(&parsed_hash)
+            .map_err(|e| AppError::Internal(format!("Invalid password hash: {}", e)))?;
+
+        let pepper = if params.keyid().is_empty() {
+            None
+        } else {
+            match &self.pepper {
+                Some(pepper) if pepper.id.as_bytes() == params.keyid() => Some(pepper),
+                _ => {
+                    tracing::error!("Password hash was made with an unknown pepper");
+                    return Ok(false);
+                }
+            }
+        };
+
+        Ok(Self::argon2(pepper, params)?
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok())
+    }
+
+    /// Whether a stored hash was made with a different algorithm, cost or pepper
+    /// than new hashes would be
+    pub fn needs_rehash(&self, password_hash: &str) -> bool {
+        let Ok(parsed_hash) = PasswordHash::new(password_hash) else {
+            return true;
+        };
+        let Ok(params) = Params::try_from(&parsed_hash) else {
+            return true;
+        };
+
+        parsed_hash.algorithm != Algorithm::Argon2id.ident()
+            || parsed_hash.version != Some(Version::V0x13.into())
+            || params.m_cost() != self.params.m_cost()
+            || params.t_cost() != self.params.t_cost()
+            || params.p_cost() != self.params.p_cost()
+            || params.keyid() != self.params.keyid()
+    }
+
+    /// Run a password check against a throwaway hash so requests for unknown accounts
+    /// take as long as real ones
+    pub fn verify_dummy(&self, password: &str) {
+        let _ = self.verify(password, &self.dummy_hash);
+    }
+
+    fn argon2(pepper: Option<&Pepper>, params: Params) -> Result<Argon2<'_>> {
+        match pepper {
+            Some(pepper) => {
+                Argon2::new_with_secret(&pepper.key, Algorithm::Argon2id, Version::V0x13, params)
+                    .map_err(|e| AppError::Internal(format!("Invalid password pepper: {}", e)))
+            }
+            None => Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(memory_kib: u32, pepper: bool) -> PasswordPolicyConfig {
+        PasswordPolicyConfig {
+            memory_kib,
+            iterations: 1,
+            parallelism: 1,
+            pepper,
+        }
+    }
+
+    fn pepper_secret(byte: u8) -> String {
+        general_purpose::STANDARD.encode([byte; 32])
+    }
+
+    #[test]
+    fn verifies_its_own_hashes() {
+        let hasher = PasswordHasher::new(&policy(64, false), None).unwrap();
+        let hash = hasher.hash("correct horse").unwrap();
+
+        assert!(hasher.verify("correct horse", &hash).unwrap());
+        assert!(!hasher.verify("wrong horse", &hash).unwrap());
+        assert!(!hasher.needs_rehash(&hash));
+    }
+
+    #[test]
+    fn peppered_hashes_carry_the_pepper_id() {
+        let secret = pepper_secret(1);
+        let hasher = PasswordHasher::new(&policy(64, true), Some(&secret)).unwrap();
+        let hash = hasher.hash("correct horse").unwrap();
+
+        assert!(hash.contains("keyid="));
+        assert!(hasher.verify("correct horse", &hash).unwrap());
+        assert!(!hasher.needs_rehash(&hash));
+    }
+
+    #[test]
+    fn peppered_hashes_need_the_same_pepper() {
+        let secret = pepper_secret(1);
+        let hash = PasswordHasher::new(&policy(64, true), Some(&secret))
+            .unwrap()
+            .hash("correct horse")
+            .unwrap();
+
+        let other = pepper_secret(2);
+        let other_pepper = PasswordHasher::new(&policy(64, true), Some(&other)).unwrap();
+        assert!(!other_pepper.verify("correct horse", &hash).unwrap());
+
+        let no_pepper = PasswordHasher::new(&policy(64, false), None).unwrap();
+        assert!(!no_pepper.verify("correct horse", &hash).unwrap());
+    }
+
+    #[test]
+    fn turning_the_pepper_on_keeps_old_hashes_working() {
+        let plain = PasswordHasher::new(&policy(64, false), None)
+            .unwrap()
+            .hash("correct horse")
+            .unwrap();
+
+        let secret = pepper_secret(1);
+        let peppered = PasswordHasher::new(&policy(64, true), Some(&secret)).unwrap();
+        assert!(peppered.verify("correct horse", &plain).unwrap());
+        assert!(peppered.needs_rehash(&plain));
+    }
+
+    #[test]
+    fn turning_the_pepper_off_keeps_peppered_hashes_working() {
+        let secret = pepper_secret(1);
+        let peppered = PasswordHasher::new(&policy(64, true), Some(&secret))
+            .unwrap()
+            .hash("correct horse")
+            .unwrap();
+
+        // The pepper is still loaded for verification while off
+        let off = PasswordHasher::new(&policy(64, false), Some(&secret)).unwrap();
+        assert!(off.verify("correct horse", &peppered).unwrap());
+        assert!(off.needs_rehash(&peppered));
+        assert!(!off.hash("correct horse").unwrap().contains("keyid="));
+    }
+
+    #[test]
+    fn cost_changes_need_a_rehash() {
+        let hash = PasswordHasher::new(&policy(64, false), None)
+            .unwrap()
+            .hash("correct horse")
+            .unwrap();
+
+        let stronger = PasswordHasher::new(&policy(128, false), None).unwrap();
+        assert!(stronger.verify("correct horse", &hash).unwrap());
+        assert!(stronger.needs_rehash(&hash));
+    }
+
+    #[test]
+    fn foreign_hashes_need_a_rehash() {
+        let hasher = PasswordHasher::new(&policy(64, false), None).unwrap();
+        let argon2i = "$argon2i$v=19$m=64,t=1,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG";
+
+        assert!(hasher.needs_rehash(argon2i));
+        assert!(hasher.needs_rehash("not a hash"));
+    }
+}
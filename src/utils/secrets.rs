@@ -1,10 +1,22 @@
 use base64::{engine::general_purpose, Engine as _};
 use rand::Rng;
+use ring::{rand::SystemRandom, signature::Ed25519KeyPair};
+use rsa::{
+    pkcs8::{EncodePrivateKey, LineEnding, SecretDocument},
+    RsaPrivateKey,
+};
 use sqlx::MySqlPool;
 
-pub async fn get_or_create_secret(pool: &MySqlPool, key_name: &str) -> Result<String, sqlx::Error> {
+use crate::utils::errors::{AppError, Result};
+
+const RSA_KEY_BITS: usize = 2048;
+
+pub async fn get_or_create_secret(
+    pool: &MySqlPool,
+    key_name: &str,
+) -> std::result::Result<String, sqlx::Error> {
     // Try to get existing secret
-    let result: Result<(String,), sqlx::Error> =
+    let result: std::result::Result<(String,), sqlx::Error> =
         sqlx::query_as("SELECT value FROM app_secrets WHERE key_name = ?")
             .bind(key_name)
             .fetch_one(pool)
@@ -31,8 +43,46 @@ pub async fn get_or_create_secret(pool: &MySqlPool, key_name: &str) -> Result<St
     }
 }
 
-fn generate_secret() -> String {
+/// Look up a secret without creating it
+pub async fn get_secret(
+    pool: &MySqlPool,
+    key_name: &str,
+) -> std::result::Result<Option<String>, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM app_secrets WHERE key_name = ?")
+            .bind(key_name)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(result.map(|(value,)| value))
+}
+
+pub(crate) fn generate_secret() -> String {
     let mut rng = rand::thread_rng();
     let bytes: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
     general_purpose::STANDARD.encode(&bytes)
 }
+
+/// New Ed25519 private key for EdDSA JWT signing, as PKCS#8 PEM
+pub fn generate_ed25519_key() -> Result<String> {
+    let der = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| AppError::internal("Failed to generate Ed25519 key"))?;
+
+    let pem = SecretDocument::try_from(der.as_ref())
+        .and_then(|document| document.to_pem("PRIVATE KEY", LineEnding::LF))
+        .map_err(|e| AppError::Internal(format!("Failed to encode Ed25519 key: {}", e)))?;
+
+    Ok(pem.to_string())
+}
+
+/// New RSA private key for RS256 JWT signing, as PKCS#8 PEM
+pub fn generate_rsa_key() -> Result<String> {
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), RSA_KEY_BITS)
+        .map_err(|e| AppError::Internal(format!("Failed to generate RSA key: {}", e)))?;
+
+    let pem = key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|e| AppError::Internal(format!("Failed to encode RSA key: {}", e)))?;
+
+    Ok(pem.to_string())
+}
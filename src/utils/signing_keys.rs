@@ -0,0 +1,384 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use rsa::{
+    pkcs8::{DecodePrivateKey, SecretDocument},
+    traits::PublicKeyParts,
+    RsaPrivateKey,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{FromRow, MySqlPool};
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+use crate::utils::{
+    errors::{AppError, Result},
+    secrets,
+};
+
+/// `app_secrets.key_name` prefix; the rest of the name is the key ID
+const KEY_NAME_PREFIX: &str = "jwt_signing_key:";
+
+/// How often the cache is refreshed so keys rotated elsewhere are picked up
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Minimum time between reloads triggered by an unknown `kid`
+const MIN_RELOAD_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Clone)]
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub created_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    /// Public half of an asymmetric key, published in the JWKS
+    jwk: Option<Jwk>,
+}
+
+impl SigningKey {
+    pub fn is_active(&self) -> bool {
+        self.retired_at.is_none()
+    }
+
+    /// Retired keys keep validating tokens until the grace period runs out
+    pub fn accepts_tokens(&self, grace: Duration) -> bool {
+        self.retired_at
+            .is_none_or(|retired_at| retired_at + grace > Utc::now())
+    }
+}
+
+#[derive(FromRow)]
+struct SigningKeyRow {
+    key_name: String,
+    value: String,
+    algorithm: Option<String>,
+    created_at: DateTime<Utc>,
+    retired_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<SigningKeyRow> for SigningKey {
+    type Error = AppError;
+
+    fn try_from(row: SigningKeyRow) -> Result<Self> {
+        let kid = row.key_name.trim_start_matches(KEY_NAME_PREFIX).to_string();
+        let invalid_key = |e: &dyn std::fmt::Display| {
+            AppError::Internal(format!("Invalid signing key {}: {}", kid, e))
+        };
+
+        let algorithm: Algorithm = row
+            .algorithm
+            .as_deref()
+            .unwrap_or("HS256")
+            .parse()
+            .map_err(|e| invalid_key(&e))?;
+
+        let (encoding_key, decoding_key, params) = match algorithm {
+            Algorithm::HS256 => (
+                EncodingKey::from_secret(row.value.as_bytes()),
+                DecodingKey::from_secret(row.value.as_bytes()),
+                None,
+            ),
+            Algorithm::EdDSA => {
+                let (_, document) =
+                    SecretDocument::from_pem(&row.value).map_err(|e| invalid_key(&e))?;
+                let key_pair =
+                    Ed25519KeyPair::from_pkcs8(document.as_bytes()).map_err(|e| invalid_key(&e))?;
+                let x = URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref());
+
+                (
+                    EncodingKey::from_ed_der(document.as_bytes()),
+                    DecodingKey::from_ed_components(&x).map_err(|e| invalid_key(&e))?,
+                    Some(AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x,
+                    })),
+                )
+            }
+            Algorithm::RS256 => {
+                let private_key =
+                    RsaPrivateKey::from_pkcs8_pem(&row.value).map_err(|e| invalid_key(&e))?;
+                let n = URL_SAFE_NO_PAD.encode(private_key.n().to_bytes_be());
+                let e = URL_SAFE_NO_PAD.encode(private_key.e().to_bytes_be());
+
+                (
+                    EncodingKey::from_rsa_pem(row.value.as_bytes()).map_err(|e| invalid_key(&e))?,
+                    DecodingKey::from_rsa_components(&n, &e).map_err(|e| invalid_key(&e))?,
+                    Some(AlgorithmParameters::RSA(RSAKeyParameters {
+                        key_type: RSAKeyType::RSA,
+                        n,
+                        e,
+                    })),
+                )
+            }
+            other => return Err(invalid_key(&format!("unsupported algorithm {:?}", other))),
+        };
+
+        let jwk = params.map(|algorithm_params| Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                key_algorithm: Some(match algorithm {
+                    Algorithm::EdDSA => KeyAlgorithm::EdDSA,
+                    _ => KeyAlgorithm::RS256,
+                }),
+                key_id: Some(kid.clone()),
+                ..CommonParameters::default()
+            },
+            algorithm: algorithm_params,
+        });
+
+        Ok(SigningKey {
+            kid,
+            algorithm,
+            created_at: row.created_at,
+            retired_at: row.retired_at,
+            encoding_key,
+            decoding_key,
+            jwk,
+        })
+    }
+}
+
+/// All signing keys, newest first
+pub async fn list_signing_keys(db: &MySqlPool) -> Result<Vec<SigningKey>> {
+    let rows: Vec<SigningKeyRow> = sqlx::query_as(
+        "SELECT key_name, value, algorithm, created_at, retired_at FROM app_secrets
+         WHERE key_name LIKE 'jwt\\_signing\\_key:%'
+         ORDER BY created_at DESC, id DESC",
+    )
+    .fetch_all(db)
+    .await?;
+
+    rows.into_iter().map(SigningKey::try_from).collect()
+}
+
+/// Create a new active key for `algorithm` and retire the previous ones
+pub async fn rotate_signing_key(db: &MySqlPool, algorithm: Algorithm) -> Result<SigningKey> {
+    let value = match algorithm {
+        Algorithm::HS256 => secrets::generate_secret(),
+        Algorithm::EdDSA => secrets::generate_ed25519_key()?,
+        Algorithm::RS256 => secrets::generate_rsa_key()?,
+        other => {
+            return Err(AppError::Internal(format!(
+                "Unsupported JWT signing algorithm {:?}; use HS256, EdDSA or RS256",
+                other
+            )))
+        }
+    };
+
+    let kid = format!(
+        "{}-{}",
+        Utc::now().format("%Y%m%d"),
+        hex::encode(rand::random::<[u8; 4]>())
+    );
+    let key_name = format!("{}{}", KEY_NAME_PREFIX, kid);
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO app_secrets (key_name, value, algorithm, description) VALUES (?, ?, ?, ?)",
+    )
+    .bind(&key_name)
+    .bind(&value)
+    .bind(format!("{:?}", algorithm))
+    .bind("JWT signing key")
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE app_secrets SET retired_at = NOW()
+         WHERE key_name LIKE 'jwt\\_signing\\_key:%' AND key_name <> ? AND retired_at IS NULL",
+    )
+    .bind(&key_name)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(kid = %kid, algorithm = ?algorithm, "Rotated JWT signing key");
+
+    list_signing_keys(db)
+        .await?
+        .into_iter()
+        .find(|key| key.kid == kid)
+        .ok_or_else(|| AppError::internal("Rotated signing key was not stored"))
+}
+
+/// Delete retired keys whose grace period has ended
+pub async fn prune_signing_keys(db: &MySqlPool, grace: Duration) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM app_secrets
+         WHERE key_name LIKE 'jwt\\_signing\\_key:%' AND retired_at IS NOT NULL AND retired_at < ?",
+    )
+    .bind(Utc::now() - grace)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// In-memory copy of the signing keys used to issue and validate JWTs.
+///
+/// The newest active key signs; any key still inside its grace period validates,
+/// always with the algorithm stored for that key rather than the token's `alg`.
+/// Keys are reloaded periodically and whenever a token names a key we haven't
+/// seen, so rotations made by another instance or `jwt_keys rotate` take effect
+/// without a restart.
+pub struct KeyRing {
+    db: MySqlPool,
+    grace: Duration,
+    cache: RwLock<CachedKeys>,
+}
+
+struct CachedKeys {
+    keys: Vec<SigningKey>,
+    loaded_at: Instant,
+}
+
+impl KeyRing {
+    /// Load the keys, creating the first one on a fresh database. Fails when the
+    /// active key doesn't use the configured algorithm: switching algorithms is a
+    /// rotation and only happens through `jwt_keys rotate`.
+    pub async fn load(db: MySqlPool, grace: Duration, algorithm: Algorithm) -> Result<Self> {
+        let mut keys = list_signing_keys(&db).await?;
+        let active_algorithm = keys
+            .iter()
+            .find(|key| key.is_active())
+            .map(|key| key.algorithm);
+        match active_algorithm {
+            Some(active) if active != algorithm => {
+                return Err(AppError::Internal(format!(
+                    "The active JWT signing key uses {:?} but JWT_ALGORITHM is {:?}; \
+                     run `jwt_keys rotate` to switch algorithms",
+                    active, algorithm
+                )));
+            }
+            Some(_) => {}
+            None => {
+                rotate_signing_key(&db, algorithm).await?;
+                keys = list_signing_keys(&db).await?;
+            }
+        }
+
+        Ok(Self {
+            db,
+            grace,
+            cache: RwLock::new(CachedKeys {
+                keys,
+                loaded_at: Instant::now(),
+            }),
+        })
+    }
+
+    pub async fn reload(&self) -> Result<()> {
+        let keys = list_signing_keys(&self.db).await?;
+
+        let mut cache = self.cache.write().await;
+        cache.keys = keys;
+        cache.loaded_at = Instant::now();
+
+        Ok(())
+    }
+
+    /// Sign `claims` with the active key, naming it in the `kid` header and the
+    /// token type in `typ`
+    pub async fn encode<T: Serialize>(&self, typ: &str, claims: &T) -> Result<String> {
+        self.reload_if_older_than(REFRESH_INTERVAL).await;
+
+        let cache = self.cache.read().await;
+        let key = cache
+            .keys
+            .iter()
+            .find(|key| key.is_active())
+            .ok_or_else(|| AppError::internal("No active JWT signing key"))?;
+
+        let header = Header {
+            typ: Some(typ.to_string()),
+            kid: Some(key.kid.clone()),
+            ..Header::new(key.algorithm)
+        };
+
+        encode(&header, claims, &key.encoding_key)
+            .map_err(|e| AppError::Internal(format!("Failed to generate JWT: {}", e)))
+    }
+
+    /// Validate a token of type `typ` against the key named in its `kid` header.
+    /// Every key signs several kinds of token, so the `typ` header is what keeps
+    /// one kind from being accepted as another.
+    pub async fn decode<T: DeserializeOwned>(&self, typ: &str, token: &str) -> Result<T> {
+        let header = decode_header(token)
+            .map_err(|e| AppError::Authentication(format!("Invalid token: {}", e)))?;
+        if header.typ.as_deref() != Some(typ) {
+            return Err(AppError::Authentication(
+                "Invalid token: wrong token type".to_string(),
+            ));
+        }
+        let kid = header
+            .kid
+            .ok_or_else(|| AppError::Authentication("Invalid token: missing key ID".to_string()))?;
+
+        let key = match self.find(&kid).await {
+            Some(key) => Some(key),
+            None => {
+                self.reload_if_older_than(MIN_RELOAD_INTERVAL).await;
+                self.find(&kid).await
+            }
+        }
+        .ok_or_else(|| {
+            AppError::Authentication("Invalid token: unknown signing key".to_string())
+        })?;
+
+        if !key.accepts_tokens(self.grace) {
+            return Err(AppError::Authentication(
+                "Invalid token: signing key has been retired".to_string(),
+            ));
+        }
+
+        let token_data = decode::<T>(token, &key.decoding_key, &Validation::new(key.algorithm))
+            .map_err(|e| AppError::Authentication(format!("Invalid token: {}", e)))?;
+
+        Ok(token_data.claims)
+    }
+
+    /// Public keys that game servers can use to verify tokens locally.
+    /// HS256 keys are secret and never published.
+    pub async fn jwks(&self) -> JwkSet {
+        self.reload_if_older_than(REFRESH_INTERVAL).await;
+
+        let cache = self.cache.read().await;
+        JwkSet {
+            keys: cache
+                .keys
+                .iter()
+                .filter(|key| key.accepts_tokens(self.grace))
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+
+    async fn find(&self, kid: &str) -> Option<SigningKey> {
+        let cache = self.cache.read().await;
+        cache.keys.iter().find(|key| key.kid == kid).cloned()
+    }
+
+    /// Refresh from the database, keeping the cached keys if that fails
+    async fn reload_if_older_than(&self, age: std::time::Duration) {
+        if self.cache.read().await.loaded_at.elapsed() < age {
+            return;
+        }
+
+        if let Err(e) = self.reload().await {
+            tracing::warn!("Failed to reload JWT signing keys: {}", e);
+        }
+    }
+}